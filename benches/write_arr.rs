@@ -14,12 +14,12 @@ fn write_vec(c: &mut Criterion) {
         vecs.push(arr);
     }
 
-    for i in 0..sizes.len() {
-        group.bench_with_input(BenchmarkId::from_parameter(sizes[i]), &i, |b, &i| {
+    for (size, vec) in sizes.iter().zip(&vecs) {
+        group.bench_with_input(BenchmarkId::from_parameter(size), vec, |b, vec| {
             b.iter(|| {
                 //black_box(File::create("vec.npy").unwrap());
                 let writer = File::create("vec.npy").unwrap();
-                vecs[i].write_npy(writer).unwrap();
+                vec.write_npy(writer).unwrap();
             })
         });
     }
@@ -0,0 +1,705 @@
+//! Conversion between `.npy` data and [Apache Arrow] arrays.
+//!
+//! The element types map as follows:
+//!
+//! * `bool`, fixed-size integers and floats (including `float16`) map to the
+//!   corresponding Arrow primitive types.
+//! * `datetime64` and `timedelta64` with units `s`, `ms`, `us` or `ns` map to
+//!   Arrow timestamps (without a time zone) and durations; `NaT` maps to null.
+//! * Subarray types and the trailing axes of N-D arrays map to (nested)
+//!   fixed-size lists, so an array of shape `(n, a, b)` becomes `n` lists of
+//!   `a` lists of `b` values.
+//! * Structured (record) types map to structs, with one child array per
+//!   field.
+//!
+//! Reading from an Arrow [`Buffer`] containing a whole `.npy` file is
+//! zero-copy when the data is in the native byte order and C order (or 1-D),
+//! aligned for the element type, and not a structured or `bool` type;
+//! otherwise the data is copied once. To read a memory-mapped file without
+//! copying, wrap the mapping in a `Buffer` with
+//! [`Buffer::from_custom_allocation`], keeping the mapping alive as the
+//! buffer's owner.
+//!
+//! This module requires the `arrow` feature.
+//!
+//! [Apache Arrow]: https://arrow.apache.org
+//! [`Buffer`]: https://docs.rs/arrow-buffer/*/arrow_buffer/buffer/struct.Buffer.html
+//! [`Buffer::from_custom_allocation`]: https://docs.rs/arrow-buffer/*/arrow_buffer/buffer/struct.Buffer.html#method.from_custom_allocation
+//!
+//! # Example
+//!
+//! ```
+//! use arrow_array::{cast::AsArray, types::Float64Type, Array};
+//! use arrow_buffer::Buffer;
+//! use ndarray::array;
+//! use ndarray_npy::arrow::{npy_to_arrow, write_arrow_npy};
+//! use ndarray_npy::{to_npy_bytes, ReadNpyExt};
+//!
+//! let npy = to_npy_bytes(&array![[1., 2., 3.], [4., 5., 6.]])?;
+//! let list = npy_to_arrow(Buffer::from_vec(npy))?;
+//! assert_eq!(list.len(), 2);
+//! let row = list.as_fixed_size_list().value(1);
+//! assert_eq!(row.as_primitive::<Float64Type>().values(), &[4., 5., 6.]);
+//!
+//! let mut npy = Vec::new();
+//! write_arrow_npy(list.as_ref(), &mut npy)?;
+//! let arr = ndarray::Array2::<f64>::read_npy(&npy[..])?;
+//! assert_eq!(arr, array![[1., 2., 3.], [4., 5., 6.]]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::dtype::{ByteOrder, DType, Field as DTypeField, RecordType, ScalarType, TypeKind};
+use crate::npy::c_order_bytes;
+use crate::{Header, ParseDTypeError, ReadNpyError, WriteNpyError};
+use arrow_array::cast::AsArray;
+use arrow_array::types::*;
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, FixedSizeListArray, PrimitiveArray, RecordBatch, StructArray,
+};
+use arrow_buffer::{Buffer, NullBuffer, ScalarBuffer};
+use arrow_schema::{DataType, Field, Fields, Schema, TimeUnit};
+use std::convert::TryFrom;
+use std::io;
+use std::mem;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// An error converting between `.npy` data and Arrow arrays.
+#[derive(Error, Debug)]
+pub enum ConvertArrowError {
+    /// An error caused by I/O.
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+
+    /// An error reading the `.npy` header or data.
+    #[error("cannot read npy data")]
+    ReadNpy(#[from] ReadNpyError),
+
+    /// An error writing the `.npy` header.
+    #[error("cannot write npy data")]
+    WriteNpy(#[from] WriteNpyError),
+
+    /// The type descriptor cannot be parsed.
+    #[error("cannot parse type descriptor")]
+    ParseDType(#[from] ParseDTypeError),
+
+    /// The `.npy` element type has no Arrow equivalent.
+    #[error("no Arrow equivalent for element type {0}")]
+    UnsupportedDType(String),
+
+    /// The Arrow data type has no `.npy` equivalent.
+    #[error("no npy equivalent for Arrow type {0}")]
+    UnsupportedArrowType(DataType),
+
+    /// The Arrow array contains nulls that cannot be represented in `.npy`
+    /// format. (Nulls in timestamps and durations are written as `NaT`.)
+    #[error("cannot write nulls of Arrow type {0}")]
+    Nulls(DataType),
+
+    /// An error constructing an Arrow array.
+    #[error("Arrow error")]
+    Arrow(#[from] arrow_schema::ArrowError),
+}
+
+/// The name of the child field of the fixed-size lists.
+const LIST_ITEM: &str = "item";
+
+/// The name of the column of a record batch created from a non-structured
+/// array.
+const VALUES_COLUMN: &str = "values";
+
+/// The `NaT` (not a time) value of `datetime64` and `timedelta64`.
+const NAT: i64 = std::i64::MIN;
+
+fn time_unit(unit: Option<&str>) -> Option<TimeUnit> {
+    Some(match unit? {
+        "s" => TimeUnit::Second,
+        "ms" => TimeUnit::Millisecond,
+        "us" => TimeUnit::Microsecond,
+        "ns" => TimeUnit::Nanosecond,
+        _ => return None,
+    })
+}
+
+fn unit_str(unit: &TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Second => "s",
+        TimeUnit::Millisecond => "ms",
+        TimeUnit::Microsecond => "us",
+        TimeUnit::Nanosecond => "ns",
+    }
+}
+
+/// Returns the Arrow type of a scalar `.npy` type.
+fn scalar_arrow_type(scalar: &ScalarType) -> Option<DataType> {
+    Some(match (scalar.kind, scalar.itemsize) {
+        (TypeKind::Bool, 1) => DataType::Boolean,
+        (TypeKind::Int, 1) => DataType::Int8,
+        (TypeKind::Int, 2) => DataType::Int16,
+        (TypeKind::Int, 4) => DataType::Int32,
+        (TypeKind::Int, 8) => DataType::Int64,
+        (TypeKind::UInt, 1) => DataType::UInt8,
+        (TypeKind::UInt, 2) => DataType::UInt16,
+        (TypeKind::UInt, 4) => DataType::UInt32,
+        (TypeKind::UInt, 8) => DataType::UInt64,
+        (TypeKind::Float, 2) => DataType::Float16,
+        (TypeKind::Float, 4) => DataType::Float32,
+        (TypeKind::Float, 8) => DataType::Float64,
+        (TypeKind::DateTime, 8) => {
+            DataType::Timestamp(time_unit(scalar.unit.as_ref().map(String::as_str))?, None)
+        }
+        (TypeKind::TimeDelta, 8) => {
+            DataType::Duration(time_unit(scalar.unit.as_ref().map(String::as_str))?)
+        }
+        _ => return None,
+    })
+}
+
+/// Returns the native-endian scalar `.npy` type of an Arrow type.
+fn npy_scalar_type(data_type: &DataType) -> Option<ScalarType> {
+    let (kind, itemsize, unit) = match data_type {
+        DataType::Boolean => (TypeKind::Bool, 1, None),
+        DataType::Int8 => (TypeKind::Int, 1, None),
+        DataType::Int16 => (TypeKind::Int, 2, None),
+        DataType::Int32 => (TypeKind::Int, 4, None),
+        DataType::Int64 => (TypeKind::Int, 8, None),
+        DataType::UInt8 => (TypeKind::UInt, 1, None),
+        DataType::UInt16 => (TypeKind::UInt, 2, None),
+        DataType::UInt32 => (TypeKind::UInt, 4, None),
+        DataType::UInt64 => (TypeKind::UInt, 8, None),
+        DataType::Float16 => (TypeKind::Float, 2, None),
+        DataType::Float32 => (TypeKind::Float, 4, None),
+        DataType::Float64 => (TypeKind::Float, 8, None),
+        DataType::Timestamp(unit, _) => (TypeKind::DateTime, 8, Some(unit_str(unit))),
+        DataType::Duration(unit) => (TypeKind::TimeDelta, 8, Some(unit_str(unit))),
+        _ => return None,
+    };
+    let mut scalar = ScalarType::native(kind, itemsize);
+    scalar.unit = unit.map(String::from);
+    Some(scalar)
+}
+
+/// Returns the Arrow type of an `.npy` element type.
+fn arrow_type(dtype: &DType) -> Result<DataType, ConvertArrowError> {
+    Ok(match dtype {
+        DType::Scalar(scalar) => scalar_arrow_type(scalar)
+            .ok_or_else(|| ConvertArrowError::UnsupportedDType(scalar.to_string()))?,
+        DType::SubArray(base, shape) => shape
+            .iter()
+            .rev()
+            .try_fold(arrow_type(base)?, |inner, &len| list_type(inner, len))?,
+        DType::Record(record) => DataType::Struct(
+            record
+                .fields
+                .iter()
+                .map(|field| {
+                    Ok(Field::new(
+                        field.name.clone(),
+                        arrow_type(&field.dtype)?,
+                        true,
+                    ))
+                })
+                .collect::<Result<Fields, ConvertArrowError>>()?,
+        ),
+    })
+}
+
+fn list_type(inner: DataType, len: usize) -> Result<DataType, ConvertArrowError> {
+    let size = i32::try_from(len).map_err(|_| ReadNpyError::LengthOverflow)?;
+    Ok(DataType::FixedSizeList(
+        Arc::new(Field::new(LIST_ITEM, inner, true)),
+        size,
+    ))
+}
+
+/// Creates a primitive array from `len` native-endian elements at the start
+/// of `data`, without copying if `data` is suitably aligned.
+fn primitive<T: ArrowPrimitiveType>(data: Buffer, len: usize) -> ArrayRef {
+    let data = if data.as_ptr().align_offset(mem::align_of::<T::Native>()) == 0 {
+        data
+    } else {
+        // Copying into a new `Buffer` aligns the data.
+        Buffer::from(data.as_slice())
+    };
+    Arc::new(PrimitiveArray::<T>::new(
+        ScalarBuffer::new(data, 0, len),
+        None,
+    ))
+}
+
+/// Creates a timestamp or duration array, mapping `NaT` to null.
+fn time<T: ArrowPrimitiveType<Native = i64>>(
+    data: Buffer,
+    len: usize,
+) -> Result<ArrayRef, ConvertArrowError> {
+    let array = primitive::<T>(data, len);
+    let values = array.as_primitive::<T>().values();
+    let nulls = if values.contains(&NAT) {
+        Some(NullBuffer::from(
+            values.iter().map(|&v| v != NAT).collect::<Vec<bool>>(),
+        ))
+    } else {
+        None
+    };
+    Ok(Arc::new(PrimitiveArray::<T>::try_new(
+        values.clone(),
+        nulls,
+    )?))
+}
+
+/// Converts `len` elements of type `dtype`, stored contiguously at the start
+/// of `data` in C order, to an Arrow array.
+fn decode(dtype: &DType, data: Buffer, len: usize) -> Result<ArrayRef, ConvertArrowError> {
+    match dtype {
+        DType::Scalar(scalar) => {
+            let data_type = arrow_type(dtype)?;
+            let data = if scalar.byte_order == ByteOrder::NATIVE
+                || scalar.byte_order == ByteOrder::NotApplicable
+            {
+                data
+            } else {
+                let mut swapped = data.as_slice()[..len * scalar.itemsize].to_vec();
+                for elem in swapped.chunks_exact_mut(scalar.itemsize) {
+                    elem.reverse();
+                }
+                Buffer::from_vec(swapped)
+            };
+            Ok(match data_type {
+                DataType::Boolean => {
+                    let bytes = &data.as_slice()[..len];
+                    if let Some(&byte) = bytes.iter().find(|&&byte| byte > 1) {
+                        return Err(
+                            ReadNpyError::from(crate::ReadDataError::ParseBoolError(byte)).into(),
+                        );
+                    }
+                    Arc::new(BooleanArray::from(
+                        bytes.iter().map(|&b| b != 0).collect::<Vec<bool>>(),
+                    ))
+                }
+                DataType::Int8 => primitive::<Int8Type>(data, len),
+                DataType::Int16 => primitive::<Int16Type>(data, len),
+                DataType::Int32 => primitive::<Int32Type>(data, len),
+                DataType::Int64 => primitive::<Int64Type>(data, len),
+                DataType::UInt8 => primitive::<UInt8Type>(data, len),
+                DataType::UInt16 => primitive::<UInt16Type>(data, len),
+                DataType::UInt32 => primitive::<UInt32Type>(data, len),
+                DataType::UInt64 => primitive::<UInt64Type>(data, len),
+                DataType::Float16 => primitive::<Float16Type>(data, len),
+                DataType::Float32 => primitive::<Float32Type>(data, len),
+                DataType::Float64 => primitive::<Float64Type>(data, len),
+                DataType::Timestamp(TimeUnit::Second, _) => time::<TimestampSecondType>(data, len)?,
+                DataType::Timestamp(TimeUnit::Millisecond, _) => {
+                    time::<TimestampMillisecondType>(data, len)?
+                }
+                DataType::Timestamp(TimeUnit::Microsecond, _) => {
+                    time::<TimestampMicrosecondType>(data, len)?
+                }
+                DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                    time::<TimestampNanosecondType>(data, len)?
+                }
+                DataType::Duration(TimeUnit::Second) => time::<DurationSecondType>(data, len)?,
+                DataType::Duration(TimeUnit::Millisecond) => {
+                    time::<DurationMillisecondType>(data, len)?
+                }
+                DataType::Duration(TimeUnit::Microsecond) => {
+                    time::<DurationMicrosecondType>(data, len)?
+                }
+                DataType::Duration(TimeUnit::Nanosecond) => {
+                    time::<DurationNanosecondType>(data, len)?
+                }
+                _ => unreachable!(),
+            })
+        }
+        DType::SubArray(base, shape) => {
+            let count = shape.iter().product::<usize>() * len;
+            let values = decode(base, data, count)?;
+            wrap_lists(values, shape, len)
+        }
+        DType::Record(record) => {
+            let mut columns = Vec::with_capacity(record.fields.len());
+            for field in &record.fields {
+                let size = field.dtype.itemsize();
+                let mut field_data = Vec::with_capacity(len * size);
+                for elem in data.as_slice()[..len * record.itemsize].chunks_exact(record.itemsize) {
+                    field_data.extend_from_slice(&elem[field.offset..field.offset + size]);
+                }
+                columns.push(decode(&field.dtype, Buffer::from_vec(field_data), len)?);
+            }
+            let fields = match arrow_type(dtype)? {
+                DataType::Struct(fields) => fields,
+                _ => unreachable!(),
+            };
+            Ok(Arc::new(StructArray::try_new_with_length(
+                fields, columns, None, len,
+            )?))
+        }
+    }
+}
+
+/// Wraps `values` in nested fixed-size lists with the given `shape`, so that
+/// the outermost list array has length `len`.
+fn wrap_lists(
+    mut values: ArrayRef,
+    shape: &[usize],
+    len: usize,
+) -> Result<ArrayRef, ConvertArrowError> {
+    let mut outer_len = len * shape.iter().product::<usize>();
+    for &size in shape.iter().rev() {
+        outer_len = outer_len.checked_div(size).unwrap_or(0);
+        let field = match list_type(values.data_type().clone(), size)? {
+            DataType::FixedSizeList(field, _) => field,
+            _ => unreachable!(),
+        };
+        values = Arc::new(FixedSizeListArray::try_new_with_length(
+            field,
+            size as i32,
+            values,
+            None,
+            if size == 0 { len } else { outer_len },
+        )?);
+    }
+    Ok(values)
+}
+
+/// Converts an `.npy` file in `buffer` to an Arrow array.
+///
+/// A 1-D `.npy` array becomes an Arrow array of the same length. For an N-D
+/// array, the first axis becomes the length of the Arrow array, and the
+/// remaining axes become nested fixed-size lists. A 0-D array becomes an
+/// Arrow array of length 1.
+///
+/// The result shares `buffer` without copying if possible; see the [module
+/// documentation](index.html).
+pub fn npy_to_arrow(buffer: Buffer) -> Result<ArrayRef, ConvertArrowError> {
+    let mut rest = buffer.as_slice();
+    let header = Header::from_reader(&mut rest).map_err(ReadNpyError::from)?;
+    let header_len = buffer.len() - rest.len();
+    let dtype = header.dtype()?;
+    let len = header
+        .shape
+        .iter()
+        .try_fold(1usize, |len, &n| len.checked_mul(n))
+        .ok_or(ReadNpyError::LengthOverflow)?;
+    let num_bytes = len
+        .checked_mul(dtype.itemsize())
+        .ok_or(ReadNpyError::LengthOverflow)?;
+    if rest.len() < num_bytes {
+        return Err(ReadNpyError::MissingData.into());
+    } else if rest.len() > num_bytes {
+        return Err(ReadNpyError::ExtraBytes(rest.len() - num_bytes).into());
+    }
+    let data = if header.fortran_order && header.shape.len() > 1 {
+        Buffer::from_vec(c_order_bytes(rest, &header.shape, dtype.itemsize()))
+    } else {
+        buffer.slice_with_length(header_len, num_bytes)
+    };
+    let values = decode(&dtype, data, len)?;
+    match header.shape.split_first() {
+        Some((&outer_len, inner_shape)) => wrap_lists(values, inner_shape, outer_len),
+        None => Ok(values),
+    }
+}
+
+/// Reads an `.npy` file from `reader` and converts it to an Arrow array.
+///
+/// The file is read into a new `Buffer`, which the result shares if possible.
+/// See [`npy_to_arrow`](fn.npy_to_arrow.html).
+pub fn read_npy_arrow<R: io::Read>(mut reader: R) -> Result<ArrayRef, ConvertArrowError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    npy_to_arrow(Buffer::from_vec(bytes))
+}
+
+/// Converts an `.npy` file in `buffer` to an Arrow record batch.
+///
+/// Each field of a 1-D structured array becomes a column. Any other array
+/// becomes a single column named `values`, as returned by
+/// [`npy_to_arrow`](fn.npy_to_arrow.html).
+pub fn npy_to_record_batch(buffer: Buffer) -> Result<RecordBatch, ConvertArrowError> {
+    let array = npy_to_arrow(buffer)?;
+    match array.data_type() {
+        DataType::Struct(_) => Ok(RecordBatch::from(array.as_struct().clone())),
+        data_type => {
+            let schema = Schema::new(vec![Field::new(VALUES_COLUMN, data_type.clone(), true)]);
+            Ok(RecordBatch::try_new(Arc::new(schema), vec![array])?)
+        }
+    }
+}
+
+/// Returns the `.npy` element type of an Arrow type.
+fn npy_dtype(data_type: &DataType) -> Result<DType, ConvertArrowError> {
+    match data_type {
+        DataType::FixedSizeList(field, size) => {
+            let size = *size as usize;
+            Ok(match npy_dtype(field.data_type())? {
+                DType::SubArray(base, mut shape) => {
+                    shape.insert(0, size);
+                    DType::SubArray(base, shape)
+                }
+                inner => DType::SubArray(Box::new(inner), vec![size]),
+            })
+        }
+        DataType::Struct(fields) => {
+            let mut offset = 0;
+            let mut record_fields = Vec::with_capacity(fields.len());
+            for field in fields {
+                let dtype = npy_dtype(field.data_type())?;
+                let size = dtype.itemsize();
+                record_fields.push(DTypeField {
+                    name: field.name().clone(),
+                    title: None,
+                    dtype,
+                    offset,
+                });
+                offset += size;
+            }
+            Ok(DType::Record(RecordType {
+                fields: record_fields,
+                itemsize: offset,
+            }))
+        }
+        _ => npy_scalar_type(data_type)
+            .map(DType::Scalar)
+            .ok_or_else(|| ConvertArrowError::UnsupportedArrowType(data_type.clone())),
+    }
+}
+
+/// Returns the native-endian bytes of the elements of `array`.
+fn encode(array: &dyn Array) -> Result<Vec<u8>, ConvertArrowError> {
+    let data_type = array.data_type();
+    let is_time = matches!(data_type, DataType::Timestamp(_, _) | DataType::Duration(_));
+    if array.null_count() > 0 && !is_time {
+        return Err(ConvertArrowError::Nulls(data_type.clone()));
+    }
+    match data_type {
+        DataType::Boolean => Ok(array
+            .as_boolean()
+            .values()
+            .iter()
+            .map(|b| b as u8)
+            .collect()),
+        DataType::FixedSizeList(_, size) => {
+            let list = array.as_fixed_size_list();
+            let start = if list.is_empty() {
+                0
+            } else {
+                list.value_offset(0) as usize
+            };
+            encode(
+                list.values()
+                    .slice(start, list.len() * *size as usize)
+                    .as_ref(),
+            )
+        }
+        DataType::Struct(_) => {
+            let columns = array
+                .as_struct()
+                .columns()
+                .iter()
+                .map(|column| {
+                    let bytes = encode(column.as_ref())?;
+                    let size = npy_dtype(column.data_type())?.itemsize();
+                    Ok((bytes, size))
+                })
+                .collect::<Result<Vec<_>, ConvertArrowError>>()?;
+            let mut out = Vec::new();
+            for index in 0..array.len() {
+                for (bytes, size) in &columns {
+                    out.extend_from_slice(&bytes[index * size..(index + 1) * size]);
+                }
+            }
+            Ok(out)
+        }
+        _ if is_time => {
+            let data = array.to_data();
+            let values: &[i64] = &data.buffer::<i64>(0)[..array.len()];
+            Ok(values
+                .iter()
+                .enumerate()
+                .flat_map(|(i, &v)| {
+                    let v = if array.is_null(i) { NAT } else { v };
+                    v.to_ne_bytes()
+                })
+                .collect())
+        }
+        _ => {
+            let width = data_type
+                .primitive_width()
+                .filter(|_| npy_scalar_type(data_type).is_some())
+                .ok_or_else(|| ConvertArrowError::UnsupportedArrowType(data_type.clone()))?;
+            let data = array.to_data();
+            let bytes = data.buffers()[0].as_slice();
+            let start = data.offset() * width;
+            Ok(bytes[start..start + array.len() * width].to_vec())
+        }
+    }
+}
+
+/// Writes an Arrow array to `writer` in `.npy` format.
+///
+/// This is the inverse of [`npy_to_arrow`](fn.npy_to_arrow.html): nested
+/// fixed-size lists become the trailing axes of the `.npy` array (unless they
+/// are nested in a struct, in which case they become subarray fields), and
+/// structs become structured arrays. The data is written in the native byte
+/// order.
+pub fn write_arrow_npy<W: io::Write>(
+    array: &dyn Array,
+    mut writer: W,
+) -> Result<(), ConvertArrowError> {
+    let mut shape = vec![array.len()];
+    let mut data_type = array.data_type();
+    while let DataType::FixedSizeList(field, size) = data_type {
+        shape.push(*size as usize);
+        data_type = field.data_type();
+    }
+    let header = Header {
+        type_descriptor: npy_dtype(data_type)?.to_descr(),
+        fortran_order: false,
+        shape,
+    };
+    let data = encode(array)?;
+    header.write(&mut writer).map_err(WriteNpyError::from)?;
+    writer.write_all(&data)?;
+    Ok(())
+}
+
+/// Writes an Arrow record batch to `writer` as a 1-D structured `.npy` array,
+/// with one field per column.
+pub fn write_record_batch_npy<W: io::Write>(
+    batch: &RecordBatch,
+    writer: W,
+) -> Result<(), ConvertArrowError> {
+    write_arrow_npy(&StructArray::from(batch.clone()), writer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConvertArrowError;
+    use super::{npy_to_arrow, npy_to_record_batch, write_arrow_npy, write_record_batch_npy};
+    use crate::datetime::{DateTime64, Nanoseconds};
+    use crate::{to_npy_bytes, ReadNpyExt, WriteNpyExt, WriteOptions};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float32Type, Int16Type, Int32Type, TimestampNanosecondType};
+    use arrow_array::{Array, Int32Array};
+    use arrow_buffer::Buffer;
+    use arrow_schema::DataType;
+    use ndarray::{array, Array1, Array3};
+
+    /// Copies `bytes` into a new `Buffer`, which is always suitably aligned.
+    fn buffer(bytes: &[u8]) -> Buffer {
+        Buffer::from(bytes)
+    }
+
+    #[test]
+    fn primitive_zero_copy() {
+        let npy = buffer(&to_npy_bytes(&array![1i32, -2, 3]).unwrap());
+        let array = npy_to_arrow(npy.clone()).unwrap();
+        let values = array.as_primitive::<Int32Type>().values();
+        assert_eq!(values.as_ref(), &[1, -2, 3]);
+        assert!(npy
+            .as_slice()
+            .as_ptr_range()
+            .contains(&values.as_ptr().cast::<u8>()));
+
+        let mut out = Vec::new();
+        write_arrow_npy(&array, &mut out).unwrap();
+        assert_eq!(out, npy.as_slice());
+    }
+
+    #[test]
+    fn n_dimensional() {
+        let arr = Array3::from_shape_fn((2, 3, 4), |(i, j, k)| (i * 100 + j * 10 + k) as i16);
+        for fortran in &[false, true] {
+            let mut npy = Vec::new();
+            let view = if *fortran {
+                arr.t().to_owned().reversed_axes()
+            } else {
+                arr.clone()
+            };
+            let options = WriteOptions::new().byte_order(crate::dtype::ByteOrder::Big);
+            view.write_npy_with_options(&mut npy, &options).unwrap();
+            let array = npy_to_arrow(buffer(&npy)).unwrap();
+            assert_eq!(array.len(), 2);
+            let row = array.as_fixed_size_list().value(1);
+            let col = row.as_fixed_size_list().value(2);
+            assert_eq!(
+                col.as_primitive::<Int16Type>().values().as_ref(),
+                &[120, 121, 122, 123]
+            );
+
+            let mut out = Vec::new();
+            write_arrow_npy(&array, &mut out).unwrap();
+            assert_eq!(Array3::<i16>::read_npy(&out[..]).unwrap(), arr);
+        }
+    }
+
+    #[test]
+    fn records() {
+        let descr = "[('id', '<i4'), ('pos', '>f4', (2,)), ('ok', '|b1')]";
+        let mut npy = crate::Header {
+            type_descriptor: descr.parse().unwrap(),
+            fortran_order: false,
+            shape: vec![2],
+        }
+        .to_bytes()
+        .unwrap();
+        for (id, pos, ok) in &[(7i32, [1.5f32, 2.5], true), (8, [-1., 0.], false)] {
+            npy.extend_from_slice(&id.to_le_bytes());
+            for p in pos {
+                npy.extend_from_slice(&p.to_be_bytes());
+            }
+            npy.push(*ok as u8);
+        }
+        let batch = npy_to_record_batch(buffer(&npy)).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema().field(1).name(), "pos");
+        assert_eq!(
+            batch
+                .column(0)
+                .as_primitive::<Int32Type>()
+                .values()
+                .as_ref(),
+            &[7, 8]
+        );
+        let pos = batch.column(1).as_fixed_size_list().value(0);
+        assert_eq!(
+            pos.as_primitive::<Float32Type>().values().as_ref(),
+            &[1.5, 2.5]
+        );
+        assert!(!batch.column(2).as_boolean().value(1));
+
+        let mut out = Vec::new();
+        write_record_batch_npy(&batch, &mut out).unwrap();
+        let round_trip = npy_to_record_batch(buffer(&out)).unwrap();
+        assert_eq!(round_trip, batch);
+    }
+
+    #[test]
+    fn timestamps_and_nulls() {
+        let times = array![
+            DateTime64::<Nanoseconds>::new(1_000),
+            DateTime64::NAT,
+            DateTime64::new(-5)
+        ];
+        let npy = to_npy_bytes(&times).unwrap();
+        let array = npy_to_arrow(buffer(&npy)).unwrap();
+        assert_eq!(
+            array.data_type(),
+            &DataType::Timestamp(arrow_schema::TimeUnit::Nanosecond, None)
+        );
+        let array = array.as_primitive::<TimestampNanosecondType>();
+        assert_eq!(array.null_count(), 1);
+        assert_eq!(array.value(2), -5);
+
+        let mut out = Vec::new();
+        write_arrow_npy(array, &mut out).unwrap();
+        let read = Array1::<DateTime64<Nanoseconds>>::read_npy(&out[..]).unwrap();
+        assert_eq!(read, times);
+
+        let ints = Int32Array::from(vec![Some(1), None]);
+        match write_arrow_npy(&ints, Vec::new()) {
+            Err(ConvertArrowError::Nulls(DataType::Int32)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,463 @@
+//! Command-line tool for inspecting and converting `.npy` and `.npz` files.
+//!
+//! This binary is built only with the `cli` feature, which requires a newer
+//! Rust version than the library (see the README).
+
+use clap::{Parser, Subcommand, ValueEnum};
+use ndarray::{Axis, IxDyn, OwnedRepr, Slice, SliceInfo, SliceOrIndex};
+use ndarray_npy::dtype::{ByteOrder, DType, TypeKind};
+use ndarray_npy::text::{txt_to_npy, write_txt, TextOptions};
+use ndarray_npy::{
+    DynArray, Header, NpzReader, NpzWriter, ReadNpyExt, Version, WriteNpyExt, WriteOptions,
+};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "ndarray-npy",
+    version,
+    about = "Inspect and convert .npy and .npz files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the element type, shape, memory order and format version of an
+    /// array, or of each member of an `.npz` file.
+    Info { path: PathBuf },
+    /// Print the first rows of an array.
+    Head {
+        path: PathBuf,
+        /// Number of rows (indices of the first axis) to print.
+        #[arg(short = 'n', long, default_value = "10")]
+        rows: usize,
+        /// Member of an `.npz` file to print.
+        #[arg(short, long)]
+        member: Option<String>,
+    },
+    /// Print a slice of an array, given as comma-separated indices or
+    /// `start:end:step` ranges per axis, e.g. `0:10,::2,3`.
+    Slice {
+        path: PathBuf,
+        spec: String,
+        /// Member of an `.npz` file to print.
+        #[arg(short, long)]
+        member: Option<String>,
+    },
+    /// List the members of an `.npz` file.
+    Ls { path: PathBuf },
+    /// Convert between `.npy` and `.npz` files, format versions, byte orders
+    /// and compression.
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+        /// Format version of the written `.npy` file.
+        #[arg(long)]
+        format_version: Option<VersionArg>,
+        /// Byte order of the written `.npy` file.
+        #[arg(long)]
+        byte_order: Option<ByteOrderArg>,
+        /// Compress the members of the written `.npz` file.
+        #[arg(long)]
+        compress: bool,
+        /// Member of an `.npz` input file to convert to `.npy`.
+        #[arg(short, long)]
+        member: Option<String>,
+    },
+    /// Write a 1-D or 2-D array as CSV to standard output or a file.
+    ToCsv {
+        path: PathBuf,
+        output: Option<PathBuf>,
+        /// Member of an `.npz` file to export.
+        #[arg(short, long)]
+        member: Option<String>,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        /// Number of digits after the decimal point of floats.
+        #[arg(short, long)]
+        precision: Option<usize>,
+        /// Header row, such as column names, to write before the data.
+        #[arg(long)]
+        header: Option<String>,
+    },
+    /// Read a CSV file into a 2-D `.npy` array.
+    FromCsv {
+        input: PathBuf,
+        output: PathBuf,
+        /// Element type of the array.
+        #[arg(long, default_value = "f64")]
+        dtype: ElementArg,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        /// Number of lines, such as a header row, to skip at the start.
+        #[arg(long, default_value = "0")]
+        skip_rows: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum VersionArg {
+    #[value(name = "1")]
+    V1,
+    #[value(name = "2")]
+    V2,
+    #[value(name = "3")]
+    V3,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ByteOrderArg {
+    Little,
+    Big,
+    Native,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ElementArg {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+/// Evaluates `$body` with `$arr` bound to the array inside `$any`.
+macro_rules! dispatch {
+    ($any:expr, $arr:ident => $body:expr) => {
+        match $any {
+            DynArray::Bool($arr) => $body,
+            DynArray::I8($arr) => $body,
+            DynArray::I16($arr) => $body,
+            DynArray::I32($arr) => $body,
+            DynArray::I64($arr) => $body,
+            DynArray::U8($arr) => $body,
+            DynArray::U16($arr) => $body,
+            DynArray::U32($arr) => $body,
+            DynArray::U64($arr) => $body,
+            DynArray::F32($arr) => $body,
+            DynArray::F64($arr) => $body,
+        }
+    };
+}
+
+/// Evaluates `$read` with the type alias `$t` set to the element type
+/// described by `$dtype`, and wraps the result in a `DynArray`.
+macro_rules! read_any {
+    ($dtype:expr, $t:ident => $read:expr) => {{
+        let dtype: &DType = $dtype;
+        macro_rules! arm {
+            ($variant:ident, $elem:ty) => {{
+                type $t = $elem;
+                DynArray::$variant($read)
+            }};
+        }
+        match dtype.as_scalar().map(|s| (s.kind, s.itemsize)) {
+            Some((TypeKind::Bool, 1)) => arm!(Bool, bool),
+            Some((TypeKind::Int, 1)) => arm!(I8, i8),
+            Some((TypeKind::Int, 2)) => arm!(I16, i16),
+            Some((TypeKind::Int, 4)) => arm!(I32, i32),
+            Some((TypeKind::Int, 8)) => arm!(I64, i64),
+            Some((TypeKind::UInt, 1)) => arm!(U8, u8),
+            Some((TypeKind::UInt, 2)) => arm!(U16, u16),
+            Some((TypeKind::UInt, 4)) => arm!(U32, u32),
+            Some((TypeKind::UInt, 8)) => arm!(U64, u64),
+            Some((TypeKind::Float, 4)) => arm!(F32, f32),
+            Some((TypeKind::Float, 8)) => arm!(F64, f64),
+            _ => return Err(format!("unsupported element type: {}", dtype).into()),
+        }
+    }};
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprint!("error: {}", err);
+        let mut source = err.source();
+        while let Some(err) = source {
+            eprint!(": {}", err);
+            source = err.source();
+        }
+        eprintln!();
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::Info { path } => info(&path),
+        Command::Head { path, rows, member } => {
+            let array = load(&path, member.as_ref().map(String::as_str))?;
+            dispatch!(array, arr => {
+                let head = if arr.ndim() == 0 {
+                    arr.view()
+                } else {
+                    let rows = rows.min(arr.len_of(Axis(0)));
+                    arr.slice_axis(Axis(0), Slice::from(..rows))
+                };
+                println!("{}", head);
+            });
+            Ok(())
+        }
+        Command::Slice { path, spec, member } => {
+            let array = load(&path, member.as_ref().map(String::as_str))?;
+            dispatch!(array, arr => {
+                let info = parse_slice(&spec, arr.shape())?;
+                println!("{}", arr.slice(info.as_ref()));
+            });
+            Ok(())
+        }
+        Command::Ls { path } => ls(&path),
+        Command::Convert {
+            input,
+            output,
+            format_version,
+            byte_order,
+            compress,
+            member,
+        } => {
+            let mut options = WriteOptions::new();
+            if let Some(version) = format_version {
+                options = options.version(match version {
+                    VersionArg::V1 => Version::V1_0,
+                    VersionArg::V2 => Version::V2_0,
+                    VersionArg::V3 => Version::V3_0,
+                });
+            }
+            if let Some(byte_order) = byte_order {
+                options = options.byte_order(match byte_order {
+                    ByteOrderArg::Little => ByteOrder::Little,
+                    ByteOrderArg::Big => ByteOrder::Big,
+                    ByteOrderArg::Native => ByteOrder::NATIVE,
+                });
+            }
+            if is_npz(&output) {
+                if format_version.is_some() || byte_order.is_some() {
+                    return Err(
+                        "--format-version and --byte-order apply only to .npy output".into(),
+                    );
+                }
+                let mut npz = if compress {
+                    NpzWriter::new_compressed(File::create(&output)?)
+                } else {
+                    NpzWriter::new(File::create(&output)?)
+                };
+                for (name, array) in load_all(&input)? {
+                    dispatch!(array, arr => npz.add_array(name, &arr)?);
+                }
+                npz.finish()?;
+            } else {
+                if compress {
+                    return Err("--compress applies only to .npz output".into());
+                }
+                let array = load(&input, member.as_ref().map(String::as_str))?;
+                let mut writer = BufWriter::new(File::create(&output)?);
+                dispatch!(array, arr => arr.write_npy_with_options(&mut writer, &options)?);
+                writer.flush()?;
+            }
+            Ok(())
+        }
+        Command::ToCsv {
+            path,
+            output,
+            member,
+            delimiter,
+            precision,
+            header,
+        } => {
+            let array = load(&path, member.as_ref().map(String::as_str))?;
+            let mut options = TextOptions::new().delimiter(delimiter).comments("");
+            if let Some(precision) = precision {
+                options = options.precision(precision);
+            }
+            if let Some(header) = header {
+                options = options.header(header);
+            }
+            let writer: Box<dyn Write> = match output {
+                Some(output) => Box::new(File::create(output)?),
+                None => Box::new(io::stdout()),
+            };
+            dispatch!(array, arr => write_txt(writer, &arr, &options)?);
+            Ok(())
+        }
+        Command::FromCsv {
+            input,
+            output,
+            dtype,
+            delimiter,
+            skip_rows,
+        } => {
+            let options = TextOptions::new().delimiter(delimiter).skip_rows(skip_rows);
+            let reader = File::open(&input)?;
+            let mut writer = BufWriter::new(File::create(&output)?);
+            match dtype {
+                ElementArg::I32 => txt_to_npy::<_, _, i32>(reader, &mut writer, &options)?,
+                ElementArg::I64 => txt_to_npy::<_, _, i64>(reader, &mut writer, &options)?,
+                ElementArg::F32 => txt_to_npy::<_, _, f32>(reader, &mut writer, &options)?,
+                ElementArg::F64 => txt_to_npy::<_, _, f64>(reader, &mut writer, &options)?,
+            }
+            writer.flush()?;
+            Ok(())
+        }
+    }
+}
+
+/// Returns `true` if the path has the `.npz` extension.
+fn is_npz(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "npz")
+}
+
+fn info(path: &Path) -> Result<()> {
+    if is_npz(path) {
+        return ls(path);
+    }
+    let (header, version) = Header::from_reader_with_version(BufReader::new(File::open(path)?))?;
+    println!(
+        "version: {}.{}",
+        version.major_version(),
+        version.minor_version()
+    );
+    match header.dtype() {
+        Ok(dtype) => println!("dtype: {}", dtype),
+        Err(_) => println!("dtype: {}", header.type_descriptor),
+    }
+    println!("shape: {:?}", header.shape);
+    println!(
+        "order: {}",
+        if header.fortran_order { "Fortran" } else { "C" }
+    );
+    Ok(())
+}
+
+fn ls(path: &Path) -> Result<()> {
+    let mut npz = NpzReader::new(File::open(path)?)?;
+    for entry in npz.entries()? {
+        let dtype = match entry.header.dtype() {
+            Ok(dtype) => dtype.to_string(),
+            Err(_) => entry.header.type_descriptor.to_string(),
+        };
+        println!(
+            "{}\t{}\t{:?}\t{}\t{} bytes\t{} compressed",
+            entry.name,
+            dtype,
+            entry.header.shape,
+            if entry.header.fortran_order { "F" } else { "C" },
+            entry.size,
+            entry.compressed_size,
+        );
+    }
+    Ok(())
+}
+
+/// Loads an array from an `.npy` file, or a member of an `.npz` file. The
+/// member may be omitted if the `.npz` file contains only one array.
+fn load(path: &Path, member: Option<&str>) -> Result<DynArray> {
+    if !is_npz(path) {
+        if member.is_some() {
+            return Err("--member applies only to .npz files".into());
+        }
+        return Ok(DynArray::read_npy(BufReader::new(File::open(path)?))?);
+    }
+
+    let mut npz = NpzReader::new(File::open(path)?)?;
+    let entries = npz.entries()?;
+    let entry = match member {
+        Some(member) => entries
+            .iter()
+            .find(|e| e.name == member || e.name == format!("{}.npy", member))
+            .ok_or_else(|| format!("no member named {:?}", member))?,
+        None if entries.len() == 1 => &entries[0],
+        None => return Err("the .npz file has several members; select one with --member".into()),
+    };
+    Ok(read_any!(&entry.header.dtype()?, T => npz.by_name::<OwnedRepr<T>, IxDyn>(&entry.name)?))
+}
+
+/// Loads all arrays from an `.npz` file, or the array from an `.npy` file
+/// named after the file stem.
+fn load_all(path: &Path) -> Result<Vec<(String, DynArray)>> {
+    if !is_npz(path) {
+        let name = path
+            .file_stem()
+            .map_or_else(|| "arr_0".into(), |stem| stem.to_string_lossy());
+        return Ok(vec![(format!("{}.npy", name), load(path, None)?)]);
+    }
+    let mut npz = NpzReader::new(File::open(path)?)?;
+    npz.entries()?
+        .into_iter()
+        .map(|entry| {
+            let array = read_any!(&entry.header.dtype()?, T => {
+                npz.by_name::<OwnedRepr<T>, IxDyn>(&entry.name)?
+            });
+            Ok((entry.name, array))
+        })
+        .collect()
+}
+
+/// Parses a slice specification like `0:10,::2,-1` for an array of the given
+/// shape. Axes without a specification are included in full.
+fn parse_slice(spec: &str, shape: &[usize]) -> Result<SliceInfo<Vec<SliceOrIndex>, IxDyn>> {
+    let parts: Vec<&str> = spec.split(',').map(str::trim).collect();
+    if parts.len() > shape.len() {
+        return Err(format!("too many indices for an array of shape {:?}", shape).into());
+    }
+    let mut indices = Vec::with_capacity(shape.len());
+    for (axis, &len) in shape.iter().enumerate() {
+        let part = parts.get(axis).copied().unwrap_or(":");
+        // Resolves a possibly negative index, checking that it lies within
+        // `0..=max`.
+        let resolve = |text: &str, max: usize| -> Result<isize> {
+            let index: isize = text.parse()?;
+            let resolved = if index < 0 {
+                index + len as isize
+            } else {
+                index
+            };
+            if resolved < 0 || resolved > max as isize {
+                return Err(format!(
+                    "index {} is out of bounds for axis {} of length {}",
+                    index, axis, len
+                )
+                .into());
+            }
+            Ok(resolved)
+        };
+        if part.contains(':') {
+            let fields: Vec<&str> = part.split(':').collect();
+            if fields.len() > 3 {
+                return Err(format!("invalid slice: {:?}", part).into());
+            }
+            let start = match fields[0] {
+                "" => 0,
+                text => resolve(text, len)?,
+            };
+            let end = match fields[1] {
+                "" => len as isize,
+                text => resolve(text, len)?.max(start),
+            };
+            let step = match fields.get(2) {
+                None | Some(&"") => 1,
+                Some(text) => text.parse()?,
+            };
+            if step == 0 {
+                return Err("slice step cannot be zero".into());
+            }
+            indices.push(SliceOrIndex::Slice {
+                start,
+                end: Some(end),
+                step,
+            });
+        } else {
+            if len == 0 {
+                return Err(format!("cannot index axis {} of length 0", axis).into());
+            }
+            indices.push(SliceOrIndex::Index(resolve(part, len - 1)?));
+        }
+    }
+    Ok(SliceInfo::new(indices)?)
+}
@@ -0,0 +1,451 @@
+//! Element types for NumPy's `datetime64` and `timedelta64` arrays.
+//!
+//! NumPy stores both types as 64-bit signed integers counting a unit of time
+//! given in the descriptor, e.g. `'<M8[ns]'` for `datetime64[ns]` or
+//! `'<m8[s]'` for `timedelta64[s]`. The unit is represented here by a type
+//! parameter implementing [`TimeUnit`], so reading an array checks that the
+//! file's unit matches, and writing an array writes the matching descriptor.
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::array;
+//! use ndarray_npy::datetime::{DateTime64, Nanoseconds};
+//! use ndarray_npy::{ReadNpyExt, WriteNpyExt};
+//! use ndarray::Array1;
+//!
+//! let times = array![DateTime64::<Nanoseconds>::new(0), DateTime64::NAT];
+//! let mut npy = Vec::new();
+//! times.write_npy(&mut npy)?;
+//! let read = Array1::<DateTime64<Nanoseconds>>::read_npy(&npy[..])?;
+//! assert_eq!(read, times);
+//! assert!(read[1].is_nat());
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! With the `chrono` feature, values can be converted to and from
+//! `chrono::NaiveDateTime` and `chrono::Duration`.
+//!
+//! [`TimeUnit`]: trait.TimeUnit.html
+
+use crate::dtype::{ByteOrder, ScalarType, TypeKind};
+use crate::npy::{check_for_extra_bytes, scalar_type};
+use crate::{ReadDataError, ReadableElement, WritableElement, WriteDataError};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use py_literal::Value as PyValue;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+/// The length of a [`TimeUnit`](trait.TimeUnit.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitLength {
+    /// A calendar unit of the given number of months.
+    Months(i64),
+    /// A fixed-length unit of the given number of attoseconds.
+    Attoseconds(i128),
+}
+
+/// A unit of time for [`DateTime64`] and [`TimeDelta64`].
+///
+/// [`DateTime64`]: struct.DateTime64.html
+/// [`TimeDelta64`]: struct.TimeDelta64.html
+pub trait TimeUnit: Copy {
+    /// The NumPy code for the unit, e.g. `"ns"`.
+    const CODE: &'static str;
+
+    /// The length of the unit.
+    const LENGTH: UnitLength;
+}
+
+macro_rules! time_units {
+    ($($name:ident, $code:expr, $doc:expr, $length:expr;)*) => {
+        $(
+            #[doc = $doc]
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct $name;
+
+            impl TimeUnit for $name {
+                const CODE: &'static str = $code;
+                const LENGTH: UnitLength = $length;
+            }
+        )*
+    };
+}
+
+const ATTOS_PER_SEC: i128 = 1_000_000_000_000_000_000;
+
+time_units! {
+    Years, "Y", "Years (`Y`).", UnitLength::Months(12);
+    Months, "M", "Months (`M`).", UnitLength::Months(1);
+    Weeks, "W", "Weeks (`W`).", UnitLength::Attoseconds(7 * 24 * 3600 * ATTOS_PER_SEC);
+    Days, "D", "Days (`D`).", UnitLength::Attoseconds(24 * 3600 * ATTOS_PER_SEC);
+    Hours, "h", "Hours (`h`).", UnitLength::Attoseconds(3600 * ATTOS_PER_SEC);
+    Minutes, "m", "Minutes (`m`).", UnitLength::Attoseconds(60 * ATTOS_PER_SEC);
+    Seconds, "s", "Seconds (`s`).", UnitLength::Attoseconds(ATTOS_PER_SEC);
+    Milliseconds, "ms", "Milliseconds (`ms`).", UnitLength::Attoseconds(ATTOS_PER_SEC / 1_000);
+    Microseconds, "us", "Microseconds (`us`).", UnitLength::Attoseconds(ATTOS_PER_SEC / 1_000_000);
+    Nanoseconds, "ns", "Nanoseconds (`ns`).", UnitLength::Attoseconds(ATTOS_PER_SEC / 1_000_000_000);
+    Picoseconds, "ps", "Picoseconds (`ps`).", UnitLength::Attoseconds(1_000_000);
+    Femtoseconds, "fs", "Femtoseconds (`fs`).", UnitLength::Attoseconds(1_000);
+    Attoseconds, "as", "Attoseconds (`as`).", UnitLength::Attoseconds(1);
+}
+
+/// NumPy's "not a time" value.
+const NAT: i64 = std::i64::MIN;
+
+macro_rules! impl_time_element {
+    ($name:ident, $kind:expr) => {
+        impl<U: TimeUnit> $name<U> {
+            /// The "not a time" (`NaT`) value.
+            pub const NAT: Self = $name {
+                value: NAT,
+                unit: PhantomData,
+            };
+
+            /// Creates a value from the number of units.
+            pub fn new(value: i64) -> Self {
+                $name {
+                    value,
+                    unit: PhantomData,
+                }
+            }
+
+            /// Returns the number of units.
+            pub fn value(self) -> i64 {
+                self.value
+            }
+
+            /// Returns `true` if this is the "not a time" (`NaT`) value.
+            pub fn is_nat(self) -> bool {
+                self.value == NAT
+            }
+
+            /// Returns the descriptor for the given byte order.
+            fn descriptor(byte_order: ByteOrder) -> String {
+                ScalarType {
+                    byte_order,
+                    kind: $kind,
+                    itemsize: 8,
+                    unit: Some(U::CODE.into()),
+                }
+                .to_string()
+            }
+        }
+
+        impl<U: TimeUnit> fmt::Debug for $name<U> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if self.is_nat() {
+                    write!(f, "{}(NaT)", stringify!($name))
+                } else {
+                    write!(f, "{}({} {})", stringify!($name), self.value, U::CODE)
+                }
+            }
+        }
+
+        impl<U: TimeUnit> ReadableElement for $name<U> {
+            fn read_to_end_exact_vec<R: io::Read>(
+                mut reader: R,
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<Vec<Self>, ReadDataError> {
+                let scalar = scalar_type(type_desc)?;
+                if !scalar.is($kind, 8) || scalar.unit.as_ref().map(String::as_str) != Some(U::CODE)
+                {
+                    return Err(ReadDataError::WrongDescriptor(type_desc.clone()));
+                }
+                let mut out = vec![0; len];
+                match scalar.byte_order {
+                    ByteOrder::Little => reader.read_i64_into::<LittleEndian>(&mut out)?,
+                    ByteOrder::Big => reader.read_i64_into::<BigEndian>(&mut out)?,
+                    ByteOrder::NotApplicable => {
+                        return Err(ReadDataError::WrongDescriptor(type_desc.clone()));
+                    }
+                }
+                check_for_extra_bytes(&mut reader)?;
+                Ok(out.into_iter().map(Self::new).collect())
+            }
+        }
+
+        // `$name<U>` is `repr(transparent)` over `i64`, so its data is written
+        // in the same way as `i64`.
+        unsafe impl<U: TimeUnit> WritableElement for $name<U> {
+            fn type_descriptor() -> PyValue {
+                if cfg!(target_endian = "little") {
+                    PyValue::String(Self::descriptor(ByteOrder::Little))
+                } else if cfg!(target_endian = "big") {
+                    PyValue::String(Self::descriptor(ByteOrder::Big))
+                } else {
+                    unreachable!()
+                }
+            }
+
+            fn write<W: io::Write>(&self, writer: W) -> Result<(), WriteDataError> {
+                self.value.write(writer)
+            }
+
+            fn write_slice<W: io::Write>(slice: &[Self], writer: W) -> Result<(), WriteDataError> {
+                // This is safe because `$name<U>` is `repr(transparent)` over
+                // `i64`.
+                let values = unsafe {
+                    std::slice::from_raw_parts(slice.as_ptr().cast::<i64>(), slice.len())
+                };
+                i64::write_slice(values, writer)
+            }
+
+            fn type_descriptor_with_order(byte_order: ByteOrder) -> Option<PyValue> {
+                match byte_order {
+                    ByteOrder::NotApplicable => Some(Self::type_descriptor()),
+                    _ => Some(PyValue::String(Self::descriptor(byte_order))),
+                }
+            }
+
+            fn write_slice_with_order<W: io::Write>(
+                slice: &[Self],
+                writer: W,
+                byte_order: ByteOrder,
+            ) -> Result<(), WriteDataError> {
+                // This is safe because `$name<U>` is `repr(transparent)` over
+                // `i64`.
+                let values = unsafe {
+                    std::slice::from_raw_parts(slice.as_ptr().cast::<i64>(), slice.len())
+                };
+                i64::write_slice_with_order(values, writer, byte_order)
+            }
+        }
+    };
+}
+
+/// A NumPy `datetime64` value: a number of units of time since the Unix
+/// epoch (1970-01-01T00:00:00).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct DateTime64<U> {
+    value: i64,
+    unit: PhantomData<U>,
+}
+
+impl_time_element!(DateTime64, TypeKind::DateTime);
+
+/// A NumPy `timedelta64` value: a signed number of units of time.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct TimeDelta64<U> {
+    value: i64,
+    unit: PhantomData<U>,
+}
+
+impl_time_element!(TimeDelta64, TypeKind::TimeDelta);
+
+#[cfg(feature = "chrono")]
+mod chrono_impl {
+    use super::{DateTime64, TimeDelta64, TimeUnit, UnitLength, ATTOS_PER_SEC};
+    use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+    use std::convert::TryFrom;
+
+    const ATTOS_PER_NANO: i128 = 1_000_000_000;
+
+    fn epoch() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(1970, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    /// Converts a number of attoseconds to a `Duration`, truncating to
+    /// nanosecond precision.
+    fn attos_to_duration(attos: i128) -> Option<Duration> {
+        let secs = i64::try_from(attos.div_euclid(ATTOS_PER_SEC)).ok()?;
+        let nanos = attos.rem_euclid(ATTOS_PER_SEC) / ATTOS_PER_NANO;
+        Duration::try_seconds(secs)?.checked_add(&Duration::nanoseconds(nanos as i64))
+    }
+
+    /// Converts a `Duration` to a number of attoseconds.
+    fn duration_to_attos(duration: Duration) -> i128 {
+        i128::from(duration.num_seconds()) * ATTOS_PER_SEC
+            + i128::from(duration.subsec_nanos()) * ATTOS_PER_NANO
+    }
+
+    impl<U: TimeUnit> DateTime64<U> {
+        /// Converts the value to a `chrono::NaiveDateTime`, truncating to
+        /// nanosecond precision.
+        ///
+        /// Returns `None` for `NaT` or if the value is out of range.
+        ///
+        /// This method requires the `chrono` feature.
+        pub fn to_naive_datetime(self) -> Option<NaiveDateTime> {
+            if self.is_nat() {
+                return None;
+            }
+            match U::LENGTH {
+                UnitLength::Months(months) => {
+                    let months = self.value.checked_mul(months)?;
+                    let year = i32::try_from(1970 + months.div_euclid(12)).ok()?;
+                    let month = months.rem_euclid(12) as u32 + 1;
+                    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+                }
+                UnitLength::Attoseconds(attos) => {
+                    let attos = i128::from(self.value).checked_mul(attos)?;
+                    epoch().checked_add_signed(attos_to_duration(attos)?)
+                }
+            }
+        }
+
+        /// Converts a `chrono::NaiveDateTime` to a value of this unit,
+        /// rounding towards negative infinity.
+        ///
+        /// Returns `None` if the value is out of range.
+        ///
+        /// This method requires the `chrono` feature.
+        pub fn from_naive_datetime(datetime: NaiveDateTime) -> Option<Self> {
+            let value = match U::LENGTH {
+                UnitLength::Months(months) => {
+                    let elapsed =
+                        (i64::from(datetime.year()) - 1970) * 12 + i64::from(datetime.month0());
+                    elapsed.div_euclid(months)
+                }
+                UnitLength::Attoseconds(attos) => {
+                    let elapsed = duration_to_attos(datetime.signed_duration_since(epoch()));
+                    i64::try_from(elapsed.div_euclid(attos)).ok()?
+                }
+            };
+            if value == super::NAT {
+                None
+            } else {
+                Some(Self::new(value))
+            }
+        }
+    }
+
+    impl<U: TimeUnit> TimeDelta64<U> {
+        /// Converts the value to a `chrono::Duration`, truncating to
+        /// nanosecond precision.
+        ///
+        /// Returns `None` for `NaT`, for calendar units (months and years),
+        /// which have no fixed length, or if the value is out of range.
+        ///
+        /// This method requires the `chrono` feature.
+        pub fn to_duration(self) -> Option<Duration> {
+            match U::LENGTH {
+                _ if self.is_nat() => None,
+                UnitLength::Months(_) => None,
+                UnitLength::Attoseconds(attos) => {
+                    attos_to_duration(i128::from(self.value).checked_mul(attos)?)
+                }
+            }
+        }
+
+        /// Converts a `chrono::Duration` to a value of this unit, rounding
+        /// towards negative infinity.
+        ///
+        /// Returns `None` for calendar units (months and years) or if the
+        /// value is out of range.
+        ///
+        /// This method requires the `chrono` feature.
+        pub fn from_duration(duration: Duration) -> Option<Self> {
+            match U::LENGTH {
+                UnitLength::Months(_) => None,
+                UnitLength::Attoseconds(attos) => {
+                    let value =
+                        i64::try_from(duration_to_attos(duration).div_euclid(attos)).ok()?;
+                    if value == super::NAT {
+                        None
+                    } else {
+                        Some(Self::new(value))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DateTime64, Days, Nanoseconds, TimeDelta64};
+    use crate::{ReadableElement, WritableElement};
+    use py_literal::Value as PyValue;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_big_endian() {
+        let data = [0, 0, 0, 0, 0, 0, 0, 3, 0x80, 0, 0, 0, 0, 0, 0, 0];
+        let type_desc = PyValue::String(String::from(">m8[D]"));
+        let out =
+            <TimeDelta64<Days>>::read_to_end_exact_vec(Cursor::new(&data), &type_desc, 2).unwrap();
+        assert_eq!(out, vec![TimeDelta64::new(3), TimeDelta64::NAT]);
+    }
+
+    #[test]
+    fn read_wrong_unit() {
+        let data = [0; 8];
+        let type_desc = PyValue::String(String::from("<M8[us]"));
+        assert!(<DateTime64<Nanoseconds>>::read_to_end_exact_vec(
+            Cursor::new(&data),
+            &type_desc,
+            1
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn descriptor() {
+        let expected = if cfg!(target_endian = "little") {
+            "<M8[ns]"
+        } else {
+            ">M8[ns]"
+        };
+        assert_eq!(
+            <DateTime64<Nanoseconds>>::type_descriptor(),
+            PyValue::String(expected.into())
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_conversions() {
+        use super::{Months, Seconds};
+        use chrono::{Duration, NaiveDate};
+
+        let datetime = NaiveDate::from_ymd_opt(2021, 3, 4)
+            .unwrap()
+            .and_hms_nano_opt(5, 6, 7, 8)
+            .unwrap();
+        let ns = DateTime64::<Nanoseconds>::from_naive_datetime(datetime).unwrap();
+        assert_eq!(ns.to_naive_datetime(), Some(datetime));
+        let months = DateTime64::<Months>::from_naive_datetime(datetime).unwrap();
+        assert_eq!(months.value(), 51 * 12 + 2);
+        assert_eq!(
+            months.to_naive_datetime(),
+            NaiveDate::from_ymd_opt(2021, 3, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+        );
+        assert_eq!(DateTime64::<Seconds>::NAT.to_naive_datetime(), None);
+
+        let delta = TimeDelta64::<Seconds>::new(-90);
+        assert_eq!(delta.to_duration(), Some(Duration::seconds(-90)));
+        assert_eq!(
+            TimeDelta64::<Days>::from_duration(Duration::hours(-1)),
+            Some(TimeDelta64::new(-1))
+        );
+        assert_eq!(TimeDelta64::<Months>::new(1).to_duration(), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_extremes() {
+        use super::{Hours, Minutes, Months, Seconds, Weeks, Years};
+
+        macro_rules! check_extremes {
+            ($($unit:ty),*) => {
+                $(
+                    for &value in &[std::i64::MAX, std::i64::MIN + 1] {
+                        assert_eq!(DateTime64::<$unit>::new(value).to_naive_datetime(), None);
+                        assert_eq!(TimeDelta64::<$unit>::new(value).to_duration(), None);
+                    }
+                )*
+            };
+        }
+        check_extremes!(Years, Months, Weeks, Days, Hours, Minutes, Seconds);
+    }
+}
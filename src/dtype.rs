@@ -0,0 +1,658 @@
+//! A typed representation of NumPy data type descriptors.
+//!
+//! The `descr` field of an `.npy` header is a Python literal describing the
+//! element type: a type string such as `'<f8'` for simple types, a list of
+//! `(name, format[, shape])` tuples for structured types, or a dict with
+//! `names`, `formats`, and optionally `offsets`, `titles`, and `itemsize`
+//! keys. [`DType::from_descr`] parses any of these forms into a [`DType`].
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray_npy::dtype::{ByteOrder, DType, TypeKind};
+//! use py_literal::Value as PyValue;
+//!
+//! let descr: PyValue = "[('time', '<M8[ns]'), ('pos', '<f4', (3,))]".parse()?;
+//! let dtype = DType::from_descr(&descr)?;
+//! let record = dtype.as_record().unwrap();
+//! assert_eq!(record.fields[1].name, "pos");
+//! assert_eq!(record.fields[1].offset, 8);
+//! assert_eq!(dtype.itemsize(), 20);
+//!
+//! let time = record.fields[0].dtype.as_scalar().unwrap();
+//! assert_eq!(time.kind, TypeKind::DateTime);
+//! assert_eq!(time.byte_order, ByteOrder::Little);
+//! assert_eq!(time.unit.as_ref().map(String::as_str), Some("ns"));
+//!
+//! assert_eq!(DType::from_descr(&dtype.to_descr())?, dtype);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`DType`]: enum.DType.html
+//! [`DType::from_descr`]: enum.DType.html#method.from_descr
+
+use crate::ParseDTypeError;
+use num_traits::ToPrimitive;
+use py_literal::Value as PyValue;
+use std::fmt;
+
+/// The byte order of a scalar type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Little endian (`<`).
+    Little,
+    /// Big endian (`>`).
+    Big,
+    /// Byte order is irrelevant for this type (`|`), e.g. for single bytes.
+    NotApplicable,
+}
+
+impl ByteOrder {
+    /// The byte order of the target platform.
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: ByteOrder = ByteOrder::Little;
+
+    /// The byte order of the target platform.
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: ByteOrder = ByteOrder::Big;
+
+    fn to_char(self) -> char {
+        match self {
+            ByteOrder::Little => '<',
+            ByteOrder::Big => '>',
+            ByteOrder::NotApplicable => '|',
+        }
+    }
+}
+
+/// The kind of a scalar type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TypeKind {
+    /// Boolean (`b`).
+    Bool,
+    /// Signed integer (`i`).
+    Int,
+    /// Unsigned integer (`u`).
+    UInt,
+    /// Floating point (`f`).
+    Float,
+    /// Complex floating point (`c`).
+    Complex,
+    /// `datetime64` (`M`).
+    DateTime,
+    /// `timedelta64` (`m`).
+    TimeDelta,
+    /// Fixed-length byte string (`S`).
+    Bytes,
+    /// Fixed-length UCS-4 string (`U`).
+    Unicode,
+    /// Raw bytes (`V`).
+    Void,
+    /// Python object (`O`).
+    Object,
+}
+
+impl TypeKind {
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            'b' => TypeKind::Bool,
+            'i' => TypeKind::Int,
+            'u' => TypeKind::UInt,
+            'f' => TypeKind::Float,
+            'c' => TypeKind::Complex,
+            'M' => TypeKind::DateTime,
+            'm' => TypeKind::TimeDelta,
+            'S' | 'a' => TypeKind::Bytes,
+            'U' => TypeKind::Unicode,
+            'V' => TypeKind::Void,
+            'O' => TypeKind::Object,
+            _ => return None,
+        })
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            TypeKind::Bool => 'b',
+            TypeKind::Int => 'i',
+            TypeKind::UInt => 'u',
+            TypeKind::Float => 'f',
+            TypeKind::Complex => 'c',
+            TypeKind::DateTime => 'M',
+            TypeKind::TimeDelta => 'm',
+            TypeKind::Bytes => 'S',
+            TypeKind::Unicode => 'U',
+            TypeKind::Void => 'V',
+            TypeKind::Object => 'O',
+        }
+    }
+}
+
+/// A scalar type described by a type string such as `'<f8'`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ScalarType {
+    /// The byte order.
+    pub byte_order: ByteOrder,
+    /// The kind of the type.
+    pub kind: TypeKind,
+    /// The size of the type in bytes. (For `Unicode`, this is four times the
+    /// number of characters.)
+    pub itemsize: usize,
+    /// The unit of a `datetime64` or `timedelta64` type, e.g. `"ns"`, if
+    /// specified.
+    pub unit: Option<String>,
+}
+
+impl ScalarType {
+    /// Creates a scalar type with the given kind and size, using the native
+    /// byte order for multi-byte types.
+    pub fn native(kind: TypeKind, itemsize: usize) -> Self {
+        let byte_order = match kind {
+            TypeKind::Bytes | TypeKind::Void => ByteOrder::NotApplicable,
+            _ if itemsize == 1 => ByteOrder::NotApplicable,
+            _ => ByteOrder::NATIVE,
+        };
+        ScalarType {
+            byte_order,
+            kind,
+            itemsize,
+            unit: None,
+        }
+    }
+
+    /// Parses a type string such as `'<f8'`, `'|S10'`, or `'<M8[ns]'`.
+    ///
+    /// The native byte order (`=`) is resolved to the byte order of the
+    /// target platform. Single-character codes such as `'d'` are also
+    /// accepted.
+    pub fn parse(typestr: &str) -> Result<Self, ParseDTypeError> {
+        let err = || ParseDTypeError::TypeString(typestr.to_owned());
+        if let Some(scalar) = Self::from_char_code(typestr) {
+            return Ok(scalar);
+        }
+
+        let mut chars = typestr.chars();
+        let (byte_order, rest) = match chars.next() {
+            Some('<') => (Some(ByteOrder::Little), chars.as_str()),
+            Some('>') => (Some(ByteOrder::Big), chars.as_str()),
+            Some('=') => (Some(ByteOrder::NATIVE), chars.as_str()),
+            Some('|') => (Some(ByteOrder::NotApplicable), chars.as_str()),
+            _ => (None, typestr),
+        };
+        let mut chars = rest.chars();
+        let kind = chars.next().and_then(TypeKind::from_char).ok_or_else(err)?;
+        let rest = chars.as_str();
+        let (size, unit) = match rest.find('[') {
+            Some(start) if rest.ends_with(']') => (
+                &rest[..start],
+                Some(rest[start + 1..rest.len() - 1].to_owned()),
+            ),
+            Some(_) => return Err(err()),
+            None => (rest, None),
+        };
+        if unit.is_some() && kind != TypeKind::DateTime && kind != TypeKind::TimeDelta {
+            return Err(err());
+        }
+        let itemsize = match (kind, size) {
+            (TypeKind::Object, "") => std::mem::size_of::<usize>(),
+            (TypeKind::DateTime, "") | (TypeKind::TimeDelta, "") => 8,
+            (TypeKind::Bool, "") => 1,
+            (_, "") => return Err(err()),
+            (TypeKind::Unicode, size) => size
+                .parse::<usize>()
+                .ok()
+                .and_then(|chars| chars.checked_mul(4))
+                .ok_or_else(err)?,
+            (_, size) => size.parse().map_err(|_| err())?,
+        };
+        let byte_order = match byte_order {
+            Some(order) => order,
+            None if itemsize == 1 || kind == TypeKind::Bytes || kind == TypeKind::Void => {
+                ByteOrder::NotApplicable
+            }
+            None => ByteOrder::NATIVE,
+        };
+        Ok(ScalarType {
+            byte_order,
+            kind,
+            itemsize,
+            unit,
+        })
+    }
+
+    /// Parses the single-character type codes accepted by `numpy.dtype`.
+    fn from_char_code(code: &str) -> Option<Self> {
+        let (kind, itemsize) = match code {
+            "?" => (TypeKind::Bool, 1),
+            "b" => (TypeKind::Int, 1),
+            "B" => (TypeKind::UInt, 1),
+            "h" => (TypeKind::Int, 2),
+            "H" => (TypeKind::UInt, 2),
+            "i" => (TypeKind::Int, 4),
+            "I" => (TypeKind::UInt, 4),
+            "q" => (TypeKind::Int, 8),
+            "Q" => (TypeKind::UInt, 8),
+            "e" => (TypeKind::Float, 2),
+            "f" => (TypeKind::Float, 4),
+            "d" => (TypeKind::Float, 8),
+            "F" => (TypeKind::Complex, 8),
+            "D" => (TypeKind::Complex, 16),
+            _ => return None,
+        };
+        Some(Self::native(kind, itemsize))
+    }
+
+    /// Returns `true` if the type matches `kind` and `itemsize`.
+    pub fn is(&self, kind: TypeKind, itemsize: usize) -> bool {
+        self.kind == kind && self.itemsize == itemsize
+    }
+}
+
+impl fmt::Display for ScalarType {
+    /// Formats the type string, e.g. `<f8`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = match self.kind {
+            TypeKind::Unicode => self.itemsize / 4,
+            _ => self.itemsize,
+        };
+        write!(
+            f,
+            "{}{}{}",
+            self.byte_order.to_char(),
+            self.kind.to_char(),
+            size
+        )?;
+        if let Some(ref unit) = self.unit {
+            write!(f, "[{}]", unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// A field of a structured type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Field {
+    /// The name of the field.
+    pub name: String,
+    /// The title of the field, if any.
+    pub title: Option<String>,
+    /// The type of the field.
+    pub dtype: DType,
+    /// The offset of the field from the start of the element in bytes.
+    pub offset: usize,
+}
+
+/// A structured (record) type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RecordType {
+    /// The fields of the type.
+    pub fields: Vec<Field>,
+    /// The size of an element in bytes, including any padding.
+    pub itemsize: usize,
+}
+
+impl RecordType {
+    /// Returns the field with the given name, if any.
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// A NumPy data type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DType {
+    /// A scalar type, e.g. `'<f8'`.
+    Scalar(ScalarType),
+    /// A subarray of fixed shape, e.g. `('<f8', (2, 3))`.
+    SubArray(Box<DType>, Vec<usize>),
+    /// A structured type.
+    Record(RecordType),
+}
+
+impl DType {
+    /// Parses the `descr` field of an `.npy` header.
+    pub fn from_descr(descr: &PyValue) -> Result<Self, ParseDTypeError> {
+        let err = || ParseDTypeError::Descriptor(descr.clone());
+        match descr {
+            PyValue::String(typestr) => Ok(DType::Scalar(ScalarType::parse(typestr)?)),
+            PyValue::List(fields) => Self::from_list(fields).map(DType::Record),
+            PyValue::Dict(dict) => Self::from_dict(dict).map(DType::Record),
+            PyValue::Tuple(tuple) => match tuple.as_slice() {
+                [base, shape] => subarray(Self::from_descr(base)?, shape).ok_or_else(err),
+                _ => Err(err()),
+            },
+            _ => Err(err()),
+        }
+    }
+
+    /// Parses the list form of a structured type descriptor.
+    fn from_list(fields: &[PyValue]) -> Result<RecordType, ParseDTypeError> {
+        let mut out = Vec::with_capacity(fields.len());
+        let mut offset: usize = 0;
+        for field in fields {
+            let err = || ParseDTypeError::Descriptor(field.clone());
+            let tuple = field.as_tuple().ok_or_else(err)?;
+            let (name, dtype) = match tuple.as_slice() {
+                [name, format] => (name, Self::from_descr(format)?),
+                [name, format, shape] => (
+                    name,
+                    subarray(Self::from_descr(format)?, shape).ok_or_else(err)?,
+                ),
+                _ => return Err(err()),
+            };
+            let (title, name) = match name {
+                PyValue::String(name) => (None, name.clone()),
+                PyValue::Tuple(pair) => match pair.as_slice() {
+                    [PyValue::String(title), PyValue::String(name)] => {
+                        (Some(title.clone()), name.clone())
+                    }
+                    _ => return Err(err()),
+                },
+                _ => return Err(err()),
+            };
+            let itemsize = dtype.itemsize();
+            // NumPy writes padding between fields as unnamed void fields.
+            if !(name.is_empty() && title.is_none()) {
+                out.push(Field {
+                    name,
+                    title,
+                    dtype,
+                    offset,
+                });
+            }
+            offset = offset.checked_add(itemsize).ok_or_else(err)?;
+        }
+        Ok(RecordType {
+            fields: out,
+            itemsize: offset,
+        })
+    }
+
+    /// Parses the dict form of a structured type descriptor.
+    fn from_dict(dict: &[(PyValue, PyValue)]) -> Result<RecordType, ParseDTypeError> {
+        let descr = || ParseDTypeError::Descriptor(PyValue::Dict(dict.to_vec()));
+        let mut names = None;
+        let mut formats = None;
+        let mut offsets = None;
+        let mut titles = None;
+        let mut itemsize = None;
+        for (key, value) in dict {
+            let list = || value.as_list().or_else(|| value.as_tuple());
+            match key.as_string().map(|s| s.as_str()) {
+                Some("names") => names = Some(list().ok_or_else(descr)?),
+                Some("formats") => formats = Some(list().ok_or_else(descr)?),
+                Some("offsets") => offsets = Some(list().ok_or_else(descr)?),
+                Some("titles") => titles = Some(list().ok_or_else(descr)?),
+                Some("itemsize") => itemsize = Some(as_usize(value).ok_or_else(descr)?),
+                Some("aligned") => {}
+                _ => return Err(ParseDTypeError::UnknownKey(key.clone())),
+            }
+        }
+        let (names, formats) = match (names, formats) {
+            (Some(names), Some(formats)) if names.len() == formats.len() => (names, formats),
+            _ => return Err(descr()),
+        };
+        let mut fields = Vec::with_capacity(names.len());
+        let mut next_offset: usize = 0;
+        let mut end: usize = 0;
+        for (i, (name, format)) in names.iter().zip(formats).enumerate() {
+            let name = name.as_string().ok_or_else(descr)?.clone();
+            let dtype = Self::from_descr(format)?;
+            let offset = match offsets {
+                Some(offsets) => offsets.get(i).and_then(as_usize).ok_or_else(descr)?,
+                None => next_offset,
+            };
+            let title = match titles.and_then(|titles| titles.get(i)) {
+                None | Some(PyValue::None) => None,
+                Some(PyValue::String(title)) => Some(title.clone()),
+                Some(_) => return Err(descr()),
+            };
+            next_offset = offset.checked_add(dtype.itemsize()).ok_or_else(descr)?;
+            end = end.max(next_offset);
+            fields.push(Field {
+                name,
+                title,
+                dtype,
+                offset,
+            });
+        }
+        let itemsize = match itemsize {
+            Some(itemsize) if itemsize < end => return Err(descr()),
+            Some(itemsize) => itemsize,
+            None => end,
+        };
+        Ok(RecordType { fields, itemsize })
+    }
+
+    /// Formats the type as a `descr` value for an `.npy` header.
+    ///
+    /// Structured types whose fields are in increasing, non-overlapping order
+    /// are formatted in list form, with padding written as unnamed void
+    /// fields; other structured types are formatted in dict form.
+    pub fn to_descr(&self) -> PyValue {
+        match self {
+            DType::Scalar(scalar) => PyValue::String(scalar.to_string()),
+            DType::SubArray(base, shape) => {
+                PyValue::Tuple(vec![base.to_descr(), format_shape(shape)])
+            }
+            DType::Record(record) => record_to_descr(record),
+        }
+    }
+
+    /// Returns the size of an element of this type in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of a subarray type overflows `usize`. This cannot
+    /// happen for types returned by [`DType::from_descr`], which rejects them.
+    ///
+    /// [`DType::from_descr`]: #method.from_descr
+    pub fn itemsize(&self) -> usize {
+        match self {
+            DType::Scalar(scalar) => scalar.itemsize,
+            DType::SubArray(base, shape) => {
+                subarray_itemsize(base, shape).expect("subarray size overflows usize")
+            }
+            DType::Record(record) => record.itemsize,
+        }
+    }
+
+    /// Returns the scalar type, if this is a scalar type.
+    pub fn as_scalar(&self) -> Option<&ScalarType> {
+        match self {
+            DType::Scalar(scalar) => Some(scalar),
+            _ => None,
+        }
+    }
+
+    /// Returns the structured type, if this is a structured type.
+    pub fn as_record(&self) -> Option<&RecordType> {
+        match self {
+            DType::Record(record) => Some(record),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DType {
+    /// Formats the type as the Python literal of its `descr`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_descr())
+    }
+}
+
+fn as_usize(value: &PyValue) -> Option<usize> {
+    value.as_integer()?.to_usize()
+}
+
+/// Creates a subarray type, or returns `None` if `shape` is invalid or the
+/// size of the type overflows `usize`.
+fn subarray(base: DType, shape: &PyValue) -> Option<DType> {
+    let shape = parse_shape(shape)?;
+    subarray_itemsize(&base, &shape)?;
+    Some(DType::SubArray(Box::new(base), shape))
+}
+
+fn subarray_itemsize(base: &DType, shape: &[usize]) -> Option<usize> {
+    shape
+        .iter()
+        .try_fold(base.itemsize(), |size, &len| size.checked_mul(len))
+}
+
+fn parse_shape(value: &PyValue) -> Option<Vec<usize>> {
+    match value {
+        PyValue::Tuple(shape) => shape.iter().map(as_usize).collect(),
+        // NumPy accepts an integer as shorthand for a 1-D shape.
+        PyValue::Integer(_) => Some(vec![as_usize(value)?]),
+        _ => None,
+    }
+}
+
+fn format_shape(shape: &[usize]) -> PyValue {
+    PyValue::Tuple(
+        shape
+            .iter()
+            .map(|&len| PyValue::Integer(len.into()))
+            .collect(),
+    )
+}
+
+fn record_to_descr(record: &RecordType) -> PyValue {
+    let is_packed = record
+        .fields
+        .windows(2)
+        .all(|pair| pair[0].offset + pair[0].dtype.itemsize() <= pair[1].offset);
+    if is_packed {
+        let padding = |len: usize| {
+            PyValue::Tuple(vec![
+                PyValue::String(String::new()),
+                PyValue::String(format!("|V{}", len)),
+            ])
+        };
+        let mut list = Vec::with_capacity(record.fields.len());
+        let mut offset = 0;
+        for field in &record.fields {
+            if field.offset > offset {
+                list.push(padding(field.offset - offset));
+            }
+            let name = match field.title {
+                Some(ref title) => PyValue::Tuple(vec![
+                    PyValue::String(title.clone()),
+                    PyValue::String(field.name.clone()),
+                ]),
+                None => PyValue::String(field.name.clone()),
+            };
+            list.push(match field.dtype {
+                DType::SubArray(ref base, ref shape) => {
+                    PyValue::Tuple(vec![name, base.to_descr(), format_shape(shape)])
+                }
+                ref dtype => PyValue::Tuple(vec![name, dtype.to_descr()]),
+            });
+            offset = field.offset + field.dtype.itemsize();
+        }
+        if record.itemsize > offset {
+            list.push(padding(record.itemsize - offset));
+        }
+        PyValue::List(list)
+    } else {
+        let strings =
+            |f: &dyn Fn(&Field) -> PyValue| PyValue::List(record.fields.iter().map(f).collect());
+        let mut dict = vec![
+            (
+                PyValue::String("names".into()),
+                strings(&|field| PyValue::String(field.name.clone())),
+            ),
+            (
+                PyValue::String("formats".into()),
+                strings(&|field| field.dtype.to_descr()),
+            ),
+            (
+                PyValue::String("offsets".into()),
+                strings(&|field| PyValue::Integer(field.offset.into())),
+            ),
+        ];
+        if record.fields.iter().any(|field| field.title.is_some()) {
+            dict.push((
+                PyValue::String("titles".into()),
+                strings(&|field| match field.title {
+                    Some(ref title) => PyValue::String(title.clone()),
+                    None => PyValue::None,
+                }),
+            ));
+        }
+        dict.push((
+            PyValue::String("itemsize".into()),
+            PyValue::Integer(record.itemsize.into()),
+        ));
+        PyValue::Dict(dict)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ByteOrder, DType, ParseDTypeError, ScalarType, TypeKind};
+    use py_literal::Value as PyValue;
+
+    fn parse(descr: &str) -> DType {
+        DType::from_descr(&descr.parse::<PyValue>().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn scalar() {
+        let scalar = ScalarType::parse(">u2").unwrap();
+        assert_eq!(scalar.byte_order, ByteOrder::Big);
+        assert!(scalar.is(TypeKind::UInt, 2));
+        assert_eq!(ScalarType::parse("<U3").unwrap().itemsize, 12);
+        assert_eq!(ScalarType::parse("<U3").unwrap().to_string(), "<U3");
+        assert_eq!(
+            ScalarType::parse("d").unwrap(),
+            ScalarType::parse("=f8").unwrap()
+        );
+        assert_eq!(
+            ScalarType::parse("|b1").unwrap(),
+            ScalarType::parse("?").unwrap()
+        );
+        assert!(ScalarType::parse("<x4").is_err());
+        assert!(ScalarType::parse("<f8[s]").is_err());
+    }
+
+    #[test]
+    fn list_with_padding_and_titles() {
+        let dtype = parse("[(('Title', 'a'), '<i4'), ('', '|V4'), ('b', '<f8', (2, 2))]");
+        let record = dtype.as_record().unwrap();
+        assert_eq!(record.fields.len(), 2);
+        assert_eq!(record.fields[0].title, Some("Title".into()));
+        assert_eq!(record.fields[1].offset, 8);
+        assert_eq!(record.itemsize, 40);
+        assert_eq!(parse(&dtype.to_string()), dtype);
+    }
+
+    #[test]
+    fn dict_form() {
+        let dtype = parse(
+            "{'names': ['x', 'y'], 'formats': ['<f4', '<f4'], 'offsets': [4, 0], 'itemsize': 12}",
+        );
+        let record = dtype.as_record().unwrap();
+        assert_eq!(record.field("x").unwrap().offset, 4);
+        assert_eq!(record.itemsize, 12);
+        assert!(dtype.to_descr().is_dict());
+        assert_eq!(parse(&dtype.to_string()), dtype);
+
+        let packed = parse("{'names': ['x', 'y'], 'formats': ['<f4', '<i2']}");
+        assert_eq!(packed, parse("[('x', '<f4'), ('y', '<i2')]"));
+    }
+
+    #[test]
+    fn subarray_overflow() {
+        for descr in &[
+            "('<f8', (4611686018427387904, 4))",
+            "[('a', '<f8', (4611686018427387904, 4))]",
+            "{'names': ['a'], 'formats': [('<f8', (4611686018427387904, 4))]}",
+            "('<f8', (2, 4611686018427387904))",
+        ] {
+            match DType::from_descr(&descr.parse::<PyValue>().unwrap()) {
+                Err(ParseDTypeError::Descriptor(_)) => {}
+                other => panic!("unexpected result for {}: {:?}", descr, other),
+            }
+        }
+    }
+}
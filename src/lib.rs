@@ -14,11 +14,68 @@
 //! [`read_npy`]: fn.read_npy.html
 //! [`write_npy`]: fn.write_npy.html
 //!
+//! Scalars, which NumPy saves as 0-D arrays, can be read and written directly
+//! with [`read_npy_scalar`] and [`write_npy_scalar`], or read from `.npz` files
+//! with [`NpzReader::scalar_by_name`].
+//!
+//! [`read_npy_scalar`]: fn.read_npy_scalar.html
+//! [`write_npy_scalar`]: fn.write_npy_scalar.html
+//! [`NpzReader::scalar_by_name`]: struct.NpzReader.html#method.scalar_by_name
+//!
+//! Besides arrays, slices and nested collections such as `Vec<Vec<f64>>` and
+//! `Vec<[i32; 3]>` can be written as N-D arrays (see [`NestedElements`]).
+//!
+//! [`NestedElements`]: trait.NestedElements.html
+//!
+//! For `.npy` data already in memory, such as a `Vec<u8>` or `bytes::Bytes`,
+//! [`from_npy_bytes`] reads an array (viewing the data in place when it is
+//! aligned and in the native byte order), [`view_npy`] only views it in place,
+//! and [`to_npy_bytes`] writes an array to a new `Vec<u8>`.
+//!
+//! [`from_npy_bytes`]: fn.from_npy_bytes.html
+//! [`view_npy`]: fn.view_npy.html
+//! [`to_npy_bytes`]: fn.to_npy_bytes.html
+//!
 //! See [`NpzReader`] and [`NpzWriter`] for reading/writing `.npz` files.
 //!
 //! [`NpzReader`]: struct.NpzReader.html
 //! [`NpzWriter`]: struct.NpzWriter.html
 //!
+//! Sparse matrices saved by `scipy.sparse.save_npz` can be read and written
+//! as [`SparseMatrix`] with [`NpzReader::read_sparse`] and
+//! [`NpzWriter::write_sparse`].
+//!
+//! [`SparseMatrix`]: enum.SparseMatrix.html
+//! [`NpzReader::read_sparse`]: struct.NpzReader.html#method.read_sparse
+//! [`NpzWriter::write_sparse`]: struct.NpzWriter.html#method.write_sparse
+//!
+//! With the `arrow` feature, the [`arrow`] module converts between `.npy` data
+//! and Apache Arrow arrays and record batches.
+//!
+//! [`arrow`]: arrow/index.html
+//!
+//! With the `safetensors` feature, the [`safetensors`] module converts between
+//! `.npy`/`.npz` files and the safetensors format.
+//!
+//! [`safetensors`]: safetensors/index.html
+//!
+//! The [`text`] module converts 1-D and 2-D arrays to and from delimited text
+//! such as CSV, like `numpy.savetxt` and `numpy.loadtxt`.
+//!
+//! [`text`]: text/index.html
+//!
+//! With the `serde` feature, the [`serde`] module embeds arrays as `.npy` bytes
+//! in other serde data formats, and [`Header`] implements `Serialize` and
+//! `Deserialize`.
+//!
+//! [`serde`]: serde/index.html
+//! [`Header`]: struct.Header.html
+//!
+//! With the `nalgebra` feature, `nalgebra` matrices and vectors implement
+//! [`WriteNpyExt`], and owned matrices (e.g. `DMatrix`, `DVector` and
+//! `Matrix3`) implement [`ReadNpyExt`]. Matrices are written in Fortran order,
+//! matching their column-major storage.
+//!
 //! See the [repository] for information about the default features and how to
 //! use this crate with Cargo.
 //!
@@ -26,25 +83,47 @@
 //!
 //! # Limitations
 //!
-//! * Parsing of `.npy` files is currently limited to files where the `descr`
-//!   field of the [header dictionary] is a Python string literal of the form
-//!   `'string'`, `"string"`, `'''string'''`, or `"""string"""`.
+//! * Reading array data is currently limited to files where the `descr` field
+//!   of the [header dictionary] is a type string such as `'<f8'`. The `descr`
+//!   of any file, including structured types, can be parsed into a [`DType`]
+//!   with [`Header::dtype`].
 //!
 //! * `WritableElement` and `ReadableElement` are currently implemented only
-//!   for fixed-size integers, floating point numbers, and `bool`.
+//!   for fixed-size integers, floating point numbers, `bool`, and the
+//!   `datetime64`/`timedelta64` types in the [`datetime`] module.
 //!
 //! The plan is to add support for more element types (including custom
 //! user-defined structs) in the future.
 //!
+//! [`datetime`]: datetime/index.html
+//! [`DType`]: dtype/enum.DType.html
+//! [`Header::dtype`]: struct.Header.html#method.dtype
 //! [header dictionary]: https://docs.scipy.org/doc/numpy/reference/generated/numpy.lib.format.html#format-version-1-0
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod datetime;
+pub mod dtype;
 mod npy;
 #[cfg(feature = "npz")]
 mod npz;
+#[cfg(feature = "safetensors")]
+pub mod safetensors;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod text;
 
+pub use crate::npy::header::{Header, Version};
 pub use crate::npy::{
-    read_npy, write_npy, NpyOutStream, NpyOutStreamBuilder, ReadDataError, ReadNpyError,
-    ReadNpyExt, ReadableElement, WritableElement, WriteDataError, WriteNpyError, WriteNpyExt,
+    from_npy_bytes, read_npy, read_npy_scalar, to_npy_bytes, view_npy, write_npy, write_npy_atomic,
+    write_npy_scalar, CloseStreamError, DropPolicy, DynArray, FormatHeaderError, NestedElements,
+    NpyLaneReader, NpyOutStream, NpyOutStreamBuilder, ParseDTypeError, ParseHeaderError,
+    ReadDataError, ReadHeaderError, ReadNpyError, ReadNpyExt, ReadableElement, ViewDataError,
+    ViewElement, ViewNpyError, WritableElement, WriteDataError, WriteHeaderError, WriteNpyError,
+    WriteNpyExt, WriteOptions,
 };
 #[cfg(feature = "npz")]
-pub use crate::npz::{NpzReader, NpzWriter, ReadNpzError, WriteNpzError};
+pub use crate::npz::{
+    CompressionMethod, CooMatrix, CsMatrix, NpzEntry, NpzReader, NpzWriter, ReadNpzError,
+    SparseMatrix, WriteNpzError,
+};
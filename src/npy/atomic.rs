@@ -0,0 +1,65 @@
+//! Helpers for atomically replacing files.
+//!
+//! Data is written to a temporary file in the same directory as the
+//! destination, which is synced to disk and renamed over the destination only
+//! once the data is complete. Since the rename is atomic, readers see either
+//! the old file or the complete new one, never a truncated file.
+
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Creates a new temporary file next to `path`, returning the file and its
+/// path.
+pub(crate) fn create_temp(path: &Path) -> io::Result<(File, PathBuf)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("path has no file name: {}", path.display()),
+        )
+    })?;
+    loop {
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let temp_path = path.with_file_name(temp_name);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((file, temp_path)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Syncs the temporary `file` to disk and renames it to `path`.
+pub(crate) fn persist(file: &File, temp_path: &Path, path: &Path) -> io::Result<()> {
+    file.sync_all()?;
+    fs::rename(temp_path, path)
+}
+
+/// Writes a file at `path` atomically using `write`, which is given the
+/// temporary file. The temporary file is removed if `write` fails.
+pub(crate) fn write_atomic<E, F>(path: &Path, write: F) -> Result<(), E>
+where
+    E: From<io::Error>,
+    F: FnOnce(&mut File) -> Result<(), E>,
+{
+    let (mut file, temp_path) = create_temp(path)?;
+    let result = write(&mut file).and_then(|()| persist(&file, &temp_path, path).map_err(E::from));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
@@ -0,0 +1,131 @@
+use super::{read_array_data, scalar_type, ReadDataError, ReadNpyError, ReadNpyExt};
+use super::{WriteNpyError, WriteNpyExt, WriteOptions};
+use crate::dtype::TypeKind;
+use crate::Header;
+use ndarray::ArrayD;
+use std::io;
+
+/// Defines `DynArray` with one variant per element type, along with the
+/// methods that dispatch on the variant.
+macro_rules! dyn_array {
+    ($($variant:ident($elem:ty) => ($kind:expr, $size:expr)),*) => {
+        /// An array whose element type is determined by the `.npy` header when
+        /// it is read.
+        ///
+        /// This is useful for reading arrays whose types are not known in
+        /// advance, e.g. all the arrays in an `.npz` file with
+        /// [`NpzReader::read_all`]. Only boolean, integer and floating-point
+        /// element types are supported; reading any other type fails with a
+        /// `WrongDescriptor` error.
+        ///
+        /// [`NpzReader::read_all`]: struct.NpzReader.html#method.read_all
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use ndarray::array;
+        /// use ndarray_npy::{to_npy_bytes, DynArray, ReadNpyExt};
+        ///
+        /// let bytes = to_npy_bytes(&array![[1u16, 2], [3, 4]])?;
+        /// match DynArray::read_npy(&bytes[..])? {
+        ///     DynArray::U16(arr) => assert_eq!(arr, array![[1, 2], [3, 4]].into_dyn()),
+        ///     other => panic!("unexpected element type: {:?}", other),
+        /// }
+        /// # Ok::<_, Box<dyn std::error::Error>>(())
+        /// ```
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum DynArray {
+            $($variant(ArrayD<$elem>),)*
+        }
+
+        impl DynArray {
+            /// Returns the shape of the array.
+            pub fn shape(&self) -> &[usize] {
+                match self {
+                    $(DynArray::$variant(arr) => arr.shape(),)*
+                }
+            }
+        }
+
+        impl ReadNpyExt for DynArray {
+            fn read_npy<R: io::Read>(mut reader: R) -> Result<Self, ReadNpyError> {
+                let header = Header::from_reader(&mut reader)?;
+                let scalar = scalar_type(&header.type_descriptor)?;
+                $(
+                    if scalar.is($kind, $size) {
+                        return Ok(DynArray::$variant(read_array_data(header, reader)?));
+                    }
+                )*
+                Err(ReadDataError::WrongDescriptor(header.type_descriptor).into())
+            }
+        }
+
+        impl WriteNpyExt for DynArray {
+            fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+                match self {
+                    $(DynArray::$variant(arr) => arr.write_npy(writer),)*
+                }
+            }
+
+            fn write_npy_with_options<W: io::Write>(
+                &self,
+                writer: W,
+                options: &WriteOptions,
+            ) -> Result<(), WriteNpyError> {
+                match self {
+                    $(DynArray::$variant(arr) => arr.write_npy_with_options(writer, options),)*
+                }
+            }
+        }
+    };
+}
+
+dyn_array!(
+    Bool(bool) => (TypeKind::Bool, 1),
+    I8(i8) => (TypeKind::Int, 1),
+    I16(i16) => (TypeKind::Int, 2),
+    I32(i32) => (TypeKind::Int, 4),
+    I64(i64) => (TypeKind::Int, 8),
+    U8(u8) => (TypeKind::UInt, 1),
+    U16(u16) => (TypeKind::UInt, 2),
+    U32(u32) => (TypeKind::UInt, 4),
+    U64(u64) => (TypeKind::UInt, 8),
+    F32(f32) => (TypeKind::Float, 4),
+    F64(f64) => (TypeKind::Float, 8)
+);
+
+#[cfg(test)]
+mod test {
+    use super::DynArray;
+    use crate::{to_npy_bytes, Header, ReadDataError, ReadNpyError, ReadNpyExt};
+    use ndarray::array;
+    use py_literal::Value as PyValue;
+
+    #[test]
+    fn read_dyn() {
+        let bytes = to_npy_bytes(&array![[1.5f32, 2.], [3., 4.]].t()).unwrap();
+        let arr = DynArray::read_npy(&bytes[..]).unwrap();
+        assert_eq!(arr.shape(), [2, 2]);
+        assert_eq!(arr, DynArray::F32(array![[1.5, 3.], [2., 4.]].into_dyn()));
+        let written = to_npy_bytes(&arr).unwrap();
+        assert_eq!(DynArray::read_npy(&written[..]).unwrap(), arr);
+
+        let bytes = to_npy_bytes(&array![true, false]).unwrap();
+        let arr = DynArray::read_npy(&bytes[..]).unwrap();
+        assert_eq!(arr, DynArray::Bool(array![true, false].into_dyn()));
+    }
+
+    #[test]
+    fn read_dyn_unsupported() {
+        let header = Header {
+            type_descriptor: PyValue::String("<c16".into()),
+            fortran_order: false,
+            shape: vec![0],
+        };
+        let bytes = header.to_bytes().unwrap();
+        match DynArray::read_npy(&bytes[..]) {
+            Err(ReadNpyError::ReadData(ReadDataError::WrongDescriptor(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::dtype::ByteOrder;
 use py_literal::{
     FormatError as PyValueFormatError, ParseError as PyValueParseError, Value as PyValue,
 };
@@ -45,6 +46,23 @@ pub enum ParseHeaderError {
     MissingNewline,
 }
 
+/// An error parsing a NumPy data type descriptor.
+#[derive(Error, Debug)]
+pub enum ParseDTypeError {
+    /// The type string is not a valid scalar type.
+    #[error("invalid type string: {0:?}")]
+    TypeString(String),
+
+    /// The descriptor is not a valid type string, list, dict, or subarray
+    /// tuple.
+    #[error("invalid descriptor: {0}")]
+    Descriptor(PyValue),
+
+    /// The dict form of the descriptor contains an unknown key.
+    #[error("unknown key in descriptor: {0}")]
+    UnknownKey(PyValue),
+}
+
 #[derive(Error, Debug)]
 pub enum ReadHeaderError {
     #[error("I/O error")]
@@ -72,6 +90,10 @@ pub enum WriteHeaderError {
 
     #[error("cannot format header")]
     Format(#[from] FormatHeaderError),
+
+    /// The existing header to be overwritten is invalid.
+    #[error("cannot parse existing header")]
+    ParseExisting(#[from] ParseHeaderError),
 }
 
 /// An error writing array data.
@@ -86,6 +108,38 @@ pub enum WriteDataError {
 
     #[error("Number of written elements ({1}) is less than the size ({0}) given by the dim")]
     TooFewElements(usize, usize),
+
+    /// The shape of an array written to a stream does not match the
+    /// corresponding dimensions of the stream's shape.
+    #[error("array shape {found:?} does not match the stream's dimensions {expected:?}")]
+    ShapeMismatch {
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+
+    /// An array written to a stream does not start at a boundary between
+    /// arrays of its shape.
+    #[error("cannot write an array of {len} elements at element {offset}, which is not a multiple of its length")]
+    Unaligned { offset: usize, len: usize },
+}
+
+/// An error closing an [`NpyOutStream`](struct.NpyOutStream.html).
+#[derive(Error, Debug)]
+pub enum CloseStreamError {
+    /// An error flushing, syncing or renaming the file.
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+
+    /// Fewer elements were written than given by the shape. `cleanup` is the
+    /// error from applying the [`DropPolicy`](enum.DropPolicy.html) to the
+    /// partial file, if any.
+    #[error("Number of written elements ({written}) is less than the size ({expected}) given by the dim")]
+    TooFewElements {
+        expected: usize,
+        written: usize,
+        #[source]
+        cleanup: Option<std::io::Error>,
+    },
 }
 
 /// An error writing a `.npy` file.
@@ -104,6 +158,22 @@ pub enum WriteNpyError {
 
     #[error(transparent)]
     WriteData(#[from] WriteDataError),
+
+    #[error(transparent)]
+    CloseStream(#[from] CloseStreamError),
+
+    /// The element type cannot be written in the requested byte order.
+    #[error("element type cannot be written in byte order {0:?}")]
+    UnsupportedByteOrder(ByteOrder),
+
+    /// The nested collections being written do not all have the same length
+    /// at each level of nesting, so they do not form an N-D array.
+    #[error("nested collections are not rectangular")]
+    NotRectangular,
+
+    /// The type does not support writing with non-default `WriteOptions`.
+    #[error("type does not support non-default write options")]
+    UnsupportedOptions,
 }
 
 /// An error reading array data.
@@ -156,6 +226,11 @@ pub enum ReadNpyError {
     #[error("ndim {1} of array did not match Dimension type with NDIM = {0:?}")]
     WrongNdim(Option<usize>, usize),
 
+    /// The shape of the array does not match the type being read, e.g. a
+    /// scalar or a statically sized `nalgebra` matrix.
+    #[error("shape {0:?} of array does not match the dimensions of the type")]
+    WrongShape(Vec<usize>),
+
     /// The type descriptor does not match the element type.
     #[error("incorrect descriptor ({0}) for this type")]
     WrongDescriptor(PyValue),
@@ -169,3 +244,50 @@ pub enum ReadNpyError {
     #[error("file had {0} extra bytes before EOF")]
     ExtraBytes(usize),
 }
+
+/// An error viewing array data in place.
+#[derive(Error, Debug)]
+pub enum ViewDataError {
+    /// The type descriptor does not match the element type.
+    #[error("incorrect descriptor ({0}) for this type")]
+    WrongDescriptor(PyValue),
+
+    /// The data is not in the native byte order, so it cannot be viewed
+    /// without converting it.
+    #[error("data is not in the native byte order")]
+    NonNativeEndian,
+
+    /// The data is not suitably aligned for the element type.
+    #[error("data is not aligned to {align} bytes")]
+    Misaligned { align: usize },
+
+    /// The buffer does not contain all the data described in the header.
+    #[error("reached end of buffer before all data")]
+    MissingData,
+
+    /// Extra bytes are present after the end of the data.
+    #[error("buffer had {0} extra bytes after the data")]
+    ExtraBytes(usize),
+
+    #[error("cannot parse value {0:#04x} as a bool")]
+    ParseBoolError(u8),
+}
+
+/// An error viewing the data of an `.npy` file in place.
+#[derive(Error, Debug)]
+pub enum ViewNpyError {
+    #[error("cannot read header")]
+    ReadHeader(#[from] ReadHeaderError),
+
+    #[error("cannot view data")]
+    ViewData(#[from] ViewDataError),
+
+    /// Overflow while computing the length of the array from the shape
+    /// described in the file header.
+    #[error("overflow computing length from shape")]
+    LengthOverflow,
+
+    /// An error caused by incorrect `Dimension` type.
+    #[error("ndim {1} of array did not match Dimension type with NDIM = {0:?}")]
+    WrongNdim(Option<usize>, usize),
+}
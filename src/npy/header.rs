@@ -1,7 +1,9 @@
 use super::error::*;
+use super::options::WriteOptions;
+use crate::dtype::DType;
 use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
 use num_traits::ToPrimitive;
-use py_literal::Value as PyValue;
+use py_literal::{FormatError as PyValueFormatError, Value as PyValue};
 use std::convert::TryFrom;
 use std::fmt;
 use std::io;
@@ -14,6 +16,9 @@ const MAGIC_STRING: &[u8] = b"\x93NUMPY";
 /// evenly divisible by this value.
 const HEADER_DIVISOR: usize = 64;
 
+/// Length of a '\n' char in bytes.
+const NEWLINE_LEN: usize = 1;
+
 struct HeaderLengthInfo {
     /// Total header length (including magic string, version number, header
     /// length value, array format description, padding, and final newline).
@@ -23,18 +28,31 @@ struct HeaderLengthInfo {
     formatted_header_len: Vec<u8>,
 }
 
+/// The header of an `.npy` file, describing the layout of the array data.
 #[derive(Clone, Debug)]
 pub struct Header {
+    /// The `descr` field of the header dictionary, describing the element
+    /// type.
     pub type_descriptor: PyValue,
+    /// Whether the data is stored in Fortran (column-major) order.
     pub fortran_order: bool,
+    /// The shape of the array.
     pub shape: Vec<usize>,
 }
 
-#[derive(Clone, Copy)]
+/// A version of the `.npy` format.
+///
+/// Version 1.0 limits the header length to 65535 bytes, version 2.0 raises the
+/// limit to 4 GiB, and version 3.0 additionally allows the header to contain
+/// UTF-8 rather than only ASCII.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
-enum Version {
+pub enum Version {
+    /// Version 1.0.
     V1_0,
+    /// Version 2.0.
     V2_0,
+    /// Version 3.0.
     V3_0,
 }
 
@@ -54,7 +72,7 @@ impl Version {
     }
 
     /// Major version number.
-    fn major_version(self) -> u8 {
+    pub fn major_version(self) -> u8 {
         match self {
             Version::V1_0 => 1,
             Version::V2_0 => 2,
@@ -62,8 +80,8 @@ impl Version {
         }
     }
 
-    /// Major version number.
-    fn minor_version(self) -> u8 {
+    /// Minor version number.
+    pub fn minor_version(self) -> u8 {
         match self {
             Version::V1_0 => 0,
             Version::V2_0 => 0,
@@ -111,22 +129,31 @@ impl Version {
         }
     }
 
+    /// Length of the magic string, version number, and `HEADER_LEN` value in
+    /// bytes.
+    fn prefix_len(self) -> usize {
+        MAGIC_STRING.len() + Version::VERSION_NUM_BYTES + self.header_len_num_bytes()
+    }
+
     /// Computes the total header length, formatted `HEADER_LEN` value, and
     /// padding length for this .npy version.
     ///
     /// `unpadded_arr_format` is the Python literal describing the array
     /// format, formatted as an ASCII string without any padding.
+    /// `min_padding` is the minimum number of padding bytes to reserve before
+    /// rounding the total length up to a multiple of `HEADER_DIVISOR`.
     ///
     /// Returns `None` if the total header length overflows `usize` or if the
     /// value of `HEADER_LEN` is too large for this .npy version.
-    fn compute_lengths(self, unpadded_arr_format: &[u8]) -> Option<HeaderLengthInfo> {
-        /// Length of a '\n' char in bytes.
-        const NEWLINE_LEN: usize = 1;
-
-        let prefix_len: usize =
-            MAGIC_STRING.len() + Version::VERSION_NUM_BYTES + self.header_len_num_bytes();
-        let unpadded_total_len: usize = prefix_len
+    fn compute_lengths(
+        self,
+        unpadded_arr_format: &[u8],
+        min_padding: usize,
+    ) -> Option<HeaderLengthInfo> {
+        let unpadded_total_len: usize = self
+            .prefix_len()
             .checked_add(unpadded_arr_format.len())?
+            .checked_add(min_padding)?
             .checked_add(NEWLINE_LEN)?;
         let pad_res = unpadded_total_len % HEADER_DIVISOR;
         let padding_len: usize = match pad_res {
@@ -134,6 +161,26 @@ impl Version {
             _ => HEADER_DIVISOR - pad_res,
         };
         let total_len: usize = unpadded_total_len.checked_add(padding_len)?;
+        self.lengths_for_total(unpadded_arr_format, total_len)
+    }
+
+    /// Computes the formatted `HEADER_LEN` value for a header of exactly
+    /// `total_len` bytes.
+    ///
+    /// Returns `None` if `unpadded_arr_format` does not fit in `total_len` bytes
+    /// or if the value of `HEADER_LEN` is too large for this .npy version.
+    fn lengths_for_total(
+        self,
+        unpadded_arr_format: &[u8],
+        total_len: usize,
+    ) -> Option<HeaderLengthInfo> {
+        let prefix_len = self.prefix_len();
+        let min_total_len = prefix_len
+            .checked_add(unpadded_arr_format.len())?
+            .checked_add(NEWLINE_LEN)?;
+        if total_len < min_total_len {
+            return None;
+        }
         let formatted_header_len = self.format_header_len(total_len - prefix_len)?;
         Some(HeaderLengthInfo {
             total_len,
@@ -204,7 +251,22 @@ impl Header {
         }
     }
 
-    pub fn from_reader<R: io::Read>(mut reader: R) -> Result<Self, ReadHeaderError> {
+    /// Parses the type descriptor into a [`DType`](../dtype/enum.DType.html).
+    pub fn dtype(&self) -> Result<DType, ParseDTypeError> {
+        DType::from_descr(&self.type_descriptor)
+    }
+
+    /// Reads and parses the header from the start of an `.npy` file, leaving
+    /// the reader positioned at the start of the array data.
+    pub fn from_reader<R: io::Read>(reader: R) -> Result<Self, ReadHeaderError> {
+        Self::from_reader_with_version(reader).map(|(header, _)| header)
+    }
+
+    /// Reads and parses the header like [`from_reader`](#method.from_reader),
+    /// also returning the format version of the file.
+    pub fn from_reader_with_version<R: io::Read>(
+        mut reader: R,
+    ) -> Result<(Self, Version), ReadHeaderError> {
         // Check for magic string.
         let mut buf = vec![0; MAGIC_STRING.len()];
         reader.read_exact(&mut buf)?;
@@ -241,7 +303,7 @@ impl Header {
             }
         };
         let arr_format: PyValue = header_str.parse().map_err(ParseHeaderError::from)?;
-        Ok(Header::from_py_value(arr_format)?)
+        Ok((Header::from_py_value(arr_format)?, version))
     }
 
     fn to_py_value(&self) -> PyValue {
@@ -266,38 +328,404 @@ impl Header {
         ])
     }
 
+    /// Formats the header, including the magic string, version number, and
+    /// padding, as it would appear at the start of an `.npy` file.
+    ///
+    /// The oldest format version that can represent the header is used.
     pub fn to_bytes(&self) -> Result<Vec<u8>, FormatHeaderError> {
-        // Metadata describing array's format as ASCII string.
-        let mut arr_format = Vec::new();
-        self.to_py_value().write_ascii(&mut arr_format)?;
+        self.to_bytes_with_options(&WriteOptions::new())
+    }
+
+    /// Formats the header like [`to_bytes`](#method.to_bytes), using the
+    /// format version selected in `options`, if any.
+    ///
+    /// Version 3.0 headers are written as UTF-8; other versions escape
+    /// non-ASCII characters. Returns `FormatHeaderError::HeaderTooLong` if the
+    /// header does not fit in the selected version.
+    pub fn to_bytes_with_options(
+        &self,
+        options: &WriteOptions,
+    ) -> Result<Vec<u8>, FormatHeaderError> {
+        let arr_format = self.format_arr(options.version)?;
 
         // Determine appropriate version based on header length, and compute
         // length information.
-        let (version, length_info) = [Version::V1_0, Version::V2_0]
+        let versions: &[Version] = match options.version {
+            Some(ref version) => std::slice::from_ref(version),
+            None => &[Version::V1_0, Version::V2_0],
+        };
+        let (version, length_info) = versions
             .iter()
-            .find_map(|&version| Some((version, version.compute_lengths(&arr_format)?)))
+            .find_map(|&version| {
+                let length_info = version.compute_lengths(&arr_format, options.header_padding)?;
+                Some((version, length_info))
+            })
             .ok_or(FormatHeaderError::HeaderTooLong)?;
 
-        // Write the header.
-        let mut out = Vec::with_capacity(length_info.total_len);
-        out.extend_from_slice(MAGIC_STRING);
-        out.push(version.major_version());
-        out.push(version.minor_version());
-        out.extend_from_slice(&length_info.formatted_header_len);
-        out.extend_from_slice(&arr_format);
-        out.resize(length_info.total_len - 1, b' ');
-        out.push(b'\n');
+        Ok(assemble(version, &arr_format, &length_info))
+    }
 
-        // Verify the length of the header.
-        debug_assert_eq!(out.len(), length_info.total_len);
-        debug_assert_eq!(out.len() % HEADER_DIVISOR, 0);
+    /// Formats the array format description for the given version. Version
+    /// 3.0 headers are formatted as UTF-8, and other versions as ASCII.
+    fn format_arr(&self, version: Option<Version>) -> Result<Vec<u8>, PyValueFormatError> {
+        let mut arr_format = Vec::new();
+        match version {
+            Some(Version::V3_0) => write_utf8(&self.to_py_value(), &mut arr_format)?,
+            _ => self.to_py_value().write_ascii(&mut arr_format)?,
+        }
+        Ok(arr_format)
+    }
 
-        Ok(out)
+    /// Writes the formatted header to `writer`.
+    pub fn write<W: io::Write>(&self, writer: W) -> Result<(), WriteHeaderError> {
+        self.write_with_options(writer, &WriteOptions::new())
     }
 
-    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), WriteHeaderError> {
-        let bytes = self.to_bytes()?;
+    /// Writes the header formatted with
+    /// [`to_bytes_with_options`](#method.to_bytes_with_options) to `writer`.
+    pub fn write_with_options<W: io::Write>(
+        &self,
+        mut writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteHeaderError> {
+        let bytes = self.to_bytes_with_options(options)?;
         writer.write_all(&bytes)?;
         Ok(())
     }
+
+    /// Overwrites the header at the start of an existing `.npy` file in
+    /// place, keeping the file's format version and header length so that the
+    /// array data does not move.
+    ///
+    /// This is typically used to update the shape of an array that grows
+    /// while it is written. Reserve room for the larger header with
+    /// [`WriteOptions::header_padding`](../struct.WriteOptions.html#method.header_padding)
+    /// when first writing the file; if the new header does not fit in the
+    /// existing one, `FormatHeaderError::HeaderTooLong` is returned and the
+    /// file is left unchanged. The position of `file` is restored afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray::array;
+    /// use ndarray_npy::{Header, WriteNpyExt, WriteOptions};
+    /// use std::io::Cursor;
+    /// # use std::error::Error;
+    ///
+    /// let mut file = Cursor::new(Vec::new());
+    /// let options = WriteOptions::new().header_padding(16);
+    /// array![1., 2.].write_npy_with_options(&mut file, &options)?;
+    ///
+    /// let mut header = Header::from_reader(&file.get_ref()[..])?;
+    /// header.shape = vec![1_000_000_000];
+    /// header.rewrite(&mut file)?;
+    /// assert_eq!(Header::from_reader(&file.get_ref()[..])?.shape, [1_000_000_000]);
+    /// # Ok::<_, Box<dyn Error>>(())
+    /// ```
+    pub fn rewrite<F>(&self, mut file: F) -> Result<(), WriteHeaderError>
+    where
+        F: io::Read + io::Write + io::Seek,
+    {
+        let position = file.seek(io::SeekFrom::Current(0))?;
+        let result = self.rewrite_at_start(&mut file);
+        // Restore the position even if rewriting failed.
+        let restored = file.seek(io::SeekFrom::Start(position));
+        result?;
+        restored?;
+        Ok(())
+    }
+
+    /// Overwrites the header as described for `rewrite`, leaving `file`
+    /// positioned anywhere.
+    fn rewrite_at_start<F>(&self, file: &mut F) -> Result<(), WriteHeaderError>
+    where
+        F: io::Read + io::Write + io::Seek,
+    {
+        file.seek(io::SeekFrom::Start(0))?;
+
+        // Read the version and length of the existing header.
+        let mut buf = vec![0; MAGIC_STRING.len()];
+        file.read_exact(&mut buf)?;
+        if buf != MAGIC_STRING {
+            return Err(ParseHeaderError::MagicString.into());
+        }
+        let mut buf = [0; Version::VERSION_NUM_BYTES];
+        file.read_exact(&mut buf)?;
+        let version = Version::from_bytes(&buf)?;
+        let header_len = match version.read_header_len(&mut *file) {
+            Ok(header_len) => header_len,
+            Err(ReadHeaderError::Io(err)) => return Err(err.into()),
+            Err(ReadHeaderError::Parse(err)) => return Err(err.into()),
+        };
+        let total_len = version
+            .prefix_len()
+            .checked_add(header_len)
+            .ok_or(FormatHeaderError::HeaderTooLong)?;
+
+        let arr_format = self
+            .format_arr(Some(version))
+            .map_err(FormatHeaderError::from)?;
+        let length_info = version
+            .lengths_for_total(&arr_format, total_len)
+            .ok_or(FormatHeaderError::HeaderTooLong)?;
+        file.seek(io::SeekFrom::Start(0))?;
+        file.write_all(&assemble(version, &arr_format, &length_info))?;
+        Ok(())
+    }
+}
+
+/// Assembles the header from its parts, padding the array format description
+/// with spaces to the total length.
+fn assemble(version: Version, arr_format: &[u8], length_info: &HeaderLengthInfo) -> Vec<u8> {
+    let mut out = Vec::with_capacity(length_info.total_len);
+    out.extend_from_slice(MAGIC_STRING);
+    out.push(version.major_version());
+    out.push(version.minor_version());
+    out.extend_from_slice(&length_info.formatted_header_len);
+    out.extend_from_slice(arr_format);
+    out.resize(length_info.total_len - NEWLINE_LEN, b' ');
+    out.push(b'\n');
+
+    // Verify the length of the header.
+    debug_assert_eq!(out.len(), length_info.total_len);
+
+    out
+}
+
+/// Writes `value` as a Python literal in UTF-8, leaving non-ASCII characters
+/// in strings unescaped, as NumPy does for version 3.0 headers.
+fn write_utf8(value: &PyValue, out: &mut Vec<u8>) -> Result<(), PyValueFormatError> {
+    fn write_items(items: &[PyValue], out: &mut Vec<u8>) -> Result<(), PyValueFormatError> {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                out.extend_from_slice(b", ");
+            }
+            write_utf8(item, out)?;
+        }
+        Ok(())
+    }
+
+    match value {
+        PyValue::String(s) => {
+            out.push(b'\'');
+            for c in s.chars() {
+                match c {
+                    '\\' => out.extend_from_slice(br"\\"),
+                    '\r' => out.extend_from_slice(br"\r"),
+                    '\n' => out.extend_from_slice(br"\n"),
+                    '\'' => out.extend_from_slice(br"\'"),
+                    c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                }
+            }
+            out.push(b'\'');
+        }
+        PyValue::Tuple(items) => {
+            out.push(b'(');
+            write_items(items, out)?;
+            if items.len() == 1 {
+                out.push(b',');
+            }
+            out.push(b')');
+        }
+        PyValue::List(items) => {
+            out.push(b'[');
+            write_items(items, out)?;
+            out.push(b']');
+        }
+        PyValue::Dict(items) => {
+            out.push(b'{');
+            for (i, (key, value)) in items.iter().enumerate() {
+                if i > 0 {
+                    out.extend_from_slice(b", ");
+                }
+                write_utf8(key, out)?;
+                out.extend_from_slice(b": ");
+                write_utf8(value, out)?;
+            }
+            out.push(b'}');
+        }
+        PyValue::Set(items) if !items.is_empty() => {
+            out.push(b'{');
+            write_items(items, out)?;
+            out.push(b'}');
+        }
+        other => other.write_ascii(out)?,
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::Header;
+    use py_literal::Value as PyValue;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// The serialized form of a `Header`. The type descriptor is stored as
+    /// its Python literal, e.g. `'<f8'`, as in the header dictionary of an
+    /// `.npy` file.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Header")]
+    struct HeaderRepr {
+        descr: String,
+        fortran_order: bool,
+        shape: Vec<usize>,
+    }
+
+    /// Requires the `serde` feature.
+    impl Serialize for Header {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            HeaderRepr {
+                descr: self.type_descriptor.to_string(),
+                fortran_order: self.fortran_order,
+                shape: self.shape.clone(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    /// Requires the `serde` feature.
+    impl<'de> Deserialize<'de> for Header {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = HeaderRepr::deserialize(deserializer)?;
+            let type_descriptor: PyValue = repr.descr.parse().map_err(de::Error::custom)?;
+            Ok(Header {
+                type_descriptor,
+                fortran_order: repr.fortran_order,
+                shape: repr.shape,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Header, Version};
+    use crate::{FormatHeaderError, ParseHeaderError, WriteHeaderError, WriteOptions};
+    use py_literal::Value as PyValue;
+    use std::io::{Cursor, Seek, SeekFrom};
+
+    fn record_header(field_name: &str) -> Header {
+        Header {
+            type_descriptor: PyValue::List(vec![PyValue::Tuple(vec![
+                PyValue::String(field_name.into()),
+                PyValue::String("<f8".into()),
+            ])]),
+            fortran_order: false,
+            shape: vec![3],
+        }
+    }
+
+    #[test]
+    fn explicit_version() {
+        let header = record_header("x");
+        for &(version, major, prefix_len) in &[
+            (Version::V1_0, 1, 10),
+            (Version::V2_0, 2, 12),
+            (Version::V3_0, 3, 12),
+        ] {
+            let bytes = header
+                .to_bytes_with_options(&WriteOptions::new().version(version))
+                .unwrap();
+            assert_eq!(bytes[6..8], [major, 0]);
+            assert_eq!(bytes[prefix_len], b'{');
+            assert_eq!(bytes.len() % 64, 0);
+            let (parsed, parsed_version) = Header::from_reader_with_version(&bytes[..]).unwrap();
+            assert_eq!(parsed.type_descriptor, header.type_descriptor);
+            assert_eq!(parsed_version, version);
+            assert_eq!(parsed_version.major_version(), major);
+        }
+    }
+
+    #[test]
+    fn version_3_utf8() {
+        let header = record_header("temp\u{e9}rature");
+        let bytes = header
+            .to_bytes_with_options(&WriteOptions::new().version(Version::V3_0))
+            .unwrap();
+        assert!(std::str::from_utf8(&bytes[12..])
+            .unwrap()
+            .contains("'temp\u{e9}rature'"));
+        let parsed = Header::from_reader(&bytes[..]).unwrap();
+        assert_eq!(parsed.type_descriptor, header.type_descriptor);
+
+        // Other versions escape non-ASCII characters.
+        let bytes = header.to_bytes().unwrap();
+        assert_eq!(bytes[6], 1);
+        assert!(bytes[10..].is_ascii());
+        let parsed = Header::from_reader(&bytes[..]).unwrap();
+        assert_eq!(parsed.type_descriptor, header.type_descriptor);
+    }
+
+    #[test]
+    fn version_too_small() {
+        let header = Header {
+            type_descriptor: PyValue::String("<f8".into()),
+            fortran_order: false,
+            shape: vec![1; 30_000],
+        };
+        assert_eq!(header.to_bytes().unwrap()[6], 2);
+        match header.to_bytes_with_options(&WriteOptions::new().version(Version::V1_0)) {
+            Err(FormatHeaderError::HeaderTooLong) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn header_padding() {
+        let header = record_header("x");
+        let unpadded = header.to_bytes().unwrap();
+        for &padding in &[0, 1, 64, 100] {
+            let bytes = header
+                .to_bytes_with_options(&WriteOptions::new().header_padding(padding))
+                .unwrap();
+            let spaces = bytes[..bytes.len() - 1]
+                .iter()
+                .rev()
+                .take_while(|&&b| b == b' ')
+                .count();
+            assert!(spaces >= padding);
+            assert!(bytes.len() >= unpadded.len());
+            assert_eq!(bytes.len() % 64, 0);
+            let parsed = Header::from_reader(&bytes[..]).unwrap();
+            assert_eq!(parsed.shape, header.shape);
+        }
+    }
+
+    #[test]
+    fn rewrite() {
+        let mut header = record_header("x");
+        let bytes = header
+            .to_bytes_with_options(&WriteOptions::new().version(Version::V2_0))
+            .unwrap();
+        let mut file = Cursor::new(bytes.clone());
+        file.seek(SeekFrom::End(0)).unwrap();
+
+        // The shape fits in the existing padding.
+        header.shape = vec![12345];
+        header.rewrite(&mut file).unwrap();
+        assert_eq!(file.position(), bytes.len() as u64);
+        assert_eq!(file.get_ref().len(), bytes.len());
+        assert_eq!(file.get_ref()[6], 2);
+        let parsed = Header::from_reader(&file.get_ref()[..]).unwrap();
+        assert_eq!(parsed.shape, [12345]);
+
+        // The shape does not fit, so the file is left unchanged.
+        let before = file.get_ref().clone();
+        header.shape = vec![1; 100];
+        match header.rewrite(&mut file) {
+            Err(WriteHeaderError::Format(FormatHeaderError::HeaderTooLong)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(file.get_ref(), &before);
+        assert_eq!(file.position(), bytes.len() as u64);
+
+        // The position is also restored if the file is not an `.npy` file.
+        let mut file = Cursor::new(vec![0; 16]);
+        file.seek(SeekFrom::Start(3)).unwrap();
+        match header.rewrite(&mut file) {
+            Err(WriteHeaderError::ParseExisting(ParseHeaderError::MagicString)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(file.position(), 3);
+    }
 }
@@ -0,0 +1,128 @@
+use super::{error::ReadNpyError, header::Header, ReadableElement};
+use ndarray::Array1;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+/// A reader that yields the lanes of an `.npy` file one at a time, in the
+/// order in which they are stored.
+///
+/// A lane is a contiguous run of elements in the file: a column along the
+/// first axis for an array in Fortran order, or a row along the last axis for
+/// an array in C order. Reading lane by lane allows processing arrays that do
+/// not fit in memory, such as a large column-major matrix written by
+/// [`NpyOutStream::write_lane`](struct.NpyOutStream.html#method.write_lane).
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{NpyLaneReader, WriteNpyExt};
+/// # use ndarray_npy::ReadNpyError;
+///
+/// let mut npy = Vec::new();
+/// array![[1, 2, 3], [4, 5, 6]].reversed_axes().write_npy(&mut npy).unwrap();
+///
+/// // The array is stored in Fortran order, so each lane is a column.
+/// let reader = NpyLaneReader::<_, i32>::new(&npy[..])?;
+/// assert_eq!(reader.lane_len(), 3);
+/// let lanes = reader.collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(lanes, vec![array![1, 2, 3], array![4, 5, 6]]);
+/// # Ok::<_, ReadNpyError>(())
+/// ```
+pub struct NpyLaneReader<R, A> {
+    reader: R,
+    header: Header,
+    lane_len: usize,
+    lane_bytes: u64,
+    num_lanes: usize,
+    read_lanes: usize,
+    _marker: PhantomData<A>,
+}
+
+impl<R: io::Read, A: ReadableElement> NpyLaneReader<R, A> {
+    /// Reads the header from `reader`, leaving it positioned at the first lane.
+    pub fn new(mut reader: R) -> Result<Self, ReadNpyError> {
+        let header = Header::from_reader(&mut reader)?;
+        let len = header
+            .shape
+            .iter()
+            .try_fold(1usize, |len, &dim| len.checked_mul(dim))
+            .filter(|&len| len <= std::isize::MAX as usize)
+            .ok_or(ReadNpyError::LengthOverflow)?;
+        let lane_len = if header.fortran_order {
+            header.shape.first()
+        } else {
+            header.shape.last()
+        }
+        .copied()
+        .unwrap_or(1);
+        let num_lanes = len.checked_div(lane_len).unwrap_or(0);
+        let itemsize = header
+            .dtype()
+            .map_err(|_| ReadNpyError::WrongDescriptor(header.type_descriptor.clone()))?
+            .itemsize();
+        let lane_bytes = lane_len
+            .checked_mul(itemsize)
+            .ok_or(ReadNpyError::LengthOverflow)? as u64;
+        Ok(NpyLaneReader {
+            reader,
+            header,
+            lane_len,
+            lane_bytes,
+            num_lanes,
+            read_lanes: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the header of the file.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the number of elements in each lane.
+    pub fn lane_len(&self) -> usize {
+        self.lane_len
+    }
+
+    /// Returns the total number of lanes in the file.
+    pub fn num_lanes(&self) -> usize {
+        self.num_lanes
+    }
+
+    /// Reads the next lane, or returns `None` if all lanes have been read.
+    pub fn read_lane(&mut self) -> Result<Option<Array1<A>>, ReadNpyError> {
+        if self.read_lanes == self.num_lanes {
+            return Ok(None);
+        }
+        let lane = A::read_to_end_exact_vec(
+            (&mut self.reader).take(self.lane_bytes),
+            &self.header.type_descriptor,
+            self.lane_len,
+        );
+        match lane {
+            Ok(lane) => {
+                self.read_lanes += 1;
+                Ok(Some(Array1::from(lane)))
+            }
+            Err(err) => {
+                // Stop after an error, since the position in the file is unknown.
+                self.read_lanes = self.num_lanes;
+                Err(err.into())
+            }
+        }
+    }
+}
+
+impl<R: io::Read, A: ReadableElement> Iterator for NpyLaneReader<R, A> {
+    type Item = Result<Array1<A>, ReadNpyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_lane().transpose()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.num_lanes - self.read_lanes;
+        (0, Some(remaining))
+    }
+}
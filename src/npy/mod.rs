@@ -1,10 +1,24 @@
+mod atomic;
+mod dyn_array;
 mod error;
 pub mod header;
+mod lanes;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_impl;
+mod nested;
+mod options;
 mod stream;
+mod view;
+pub use dyn_array::DynArray;
 pub use error::*;
+pub use lanes::NpyLaneReader;
+pub use nested::NestedElements;
+pub use options::WriteOptions;
 pub use stream::*;
+pub use view::{from_npy_bytes, to_npy_bytes, view_npy, ViewElement};
 
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use crate::dtype::{ByteOrder, DType, ScalarType, TypeKind};
+use byteorder::{BigEndian, ByteOrder as _, LittleEndian, ReadBytesExt};
 use header::Header;
 use ndarray::prelude::*;
 use ndarray::{Data, DataOwned, IntoDimension};
@@ -63,7 +77,104 @@ where
     array.write_npy(std::fs::File::create(path)?)
 }
 
+/// Writes an array to an `.npy` file at the specified path atomically.
+///
+/// The array is written to a temporary file in the same directory, which is
+/// synced to disk and then renamed to `path`. If writing fails, the temporary
+/// file is removed and any existing file at `path` is left untouched, so
+/// readers never see a partially written file.
+///
+/// # Example
+///
+/// ```no_run
+/// use ndarray::array;
+/// use ndarray_npy::write_npy_atomic;
+/// # use ndarray_npy::WriteNpyError;
+///
+/// let arr = array![[1, 2, 3], [4, 5, 6]];
+/// write_npy_atomic("array.npy", &arr)?;
+/// # Ok::<_, WriteNpyError>(())
+/// ```
+pub fn write_npy_atomic<P, T>(path: P, array: &T) -> Result<(), WriteNpyError>
+where
+    P: AsRef<std::path::Path>,
+    T: WriteNpyExt,
+{
+    atomic::write_atomic(path.as_ref(), |file| {
+        let mut writer = io::BufWriter::new(file);
+        array.write_npy(&mut writer)?;
+        io::Write::flush(&mut writer)?;
+        Ok(())
+    })
+}
+
+/// Reads a scalar from an `.npy` file located at the specified path.
+///
+/// The array in the file must have shape `()` (a 0-D array, as NumPy saves
+/// scalars) or `(1,)`; otherwise, `ReadNpyError::WrongShape` is returned. This
+/// is more convenient than reading an `Array0` and calling `into_scalar`.
+///
+/// # Example
+///
+/// ```no_run
+/// use ndarray_npy::read_npy_scalar;
+/// # use ndarray_npy::ReadNpyError;
+///
+/// let learning_rate: f64 = read_npy_scalar("learning_rate.npy")?;
+/// # println!("learning_rate = {}", learning_rate);
+/// # Ok::<_, ReadNpyError>(())
+/// ```
+pub fn read_npy_scalar<P, T>(path: P) -> Result<T, ReadNpyError>
+where
+    P: AsRef<std::path::Path>,
+    T: ReadableElement,
+{
+    read_scalar(std::fs::File::open(path)?)
+}
+
+/// Reads a scalar stored as an array of shape `()` or `(1,)` from `reader`.
+pub(crate) fn read_scalar<R, T>(mut reader: R) -> Result<T, ReadNpyError>
+where
+    R: io::Read,
+    T: ReadableElement,
+{
+    let header = Header::from_reader(&mut reader)?;
+    match *header.shape.as_slice() {
+        [] | [1] => {}
+        _ => return Err(ReadNpyError::WrongShape(header.shape)),
+    }
+    let mut data = T::read_to_end_exact_vec(&mut reader, &header.type_descriptor, 1)?;
+    Ok(data.pop().unwrap())
+}
+
+/// Writes a scalar as a 0-D array to an `.npy` file at the specified path.
+///
+/// This function will create the file if it does not exist, or overwrite it if
+/// it does. The file can be read with `numpy.load` as a 0-D array.
+///
+/// # Example
+///
+/// ```no_run
+/// use ndarray_npy::write_npy_scalar;
+/// # use ndarray_npy::WriteNpyError;
+///
+/// write_npy_scalar("learning_rate.npy", &0.01)?;
+/// # Ok::<_, WriteNpyError>(())
+/// ```
+pub fn write_npy_scalar<P, T>(path: P, value: &T) -> Result<(), WriteNpyError>
+where
+    P: AsRef<std::path::Path>,
+    T: WritableElement,
+{
+    aview0(value).write_npy(std::fs::File::create(path)?)
+}
+
 /// An array element type that can be written to an `.npy` or `.npz` file.
+///
+/// # Safety
+///
+/// Implementors must ensure that `write` and `write_slice` write exactly the
+/// bytes described by `type_descriptor`.
 pub unsafe trait WritableElement: Sized {
     /// Returns a descriptor of the type that can be used in the header.
     fn type_descriptor() -> PyValue;
@@ -73,6 +184,33 @@ pub unsafe trait WritableElement: Sized {
 
     /// Writes a slice of `Self` to the writer.
     fn write_slice<W: io::Write>(slice: &[Self], writer: W) -> Result<(), WriteDataError>;
+
+    /// Returns a descriptor of the type with the given byte order, or `None`
+    /// if the type cannot be written in that byte order.
+    ///
+    /// The default implementation supports only the native byte order (and
+    /// treats `ByteOrder::NotApplicable` as native).
+    fn type_descriptor_with_order(byte_order: ByteOrder) -> Option<PyValue> {
+        if byte_order == ByteOrder::NATIVE || byte_order == ByteOrder::NotApplicable {
+            Some(Self::type_descriptor())
+        } else {
+            None
+        }
+    }
+
+    /// Writes a slice of `Self` to the writer in the given byte order.
+    ///
+    /// This is called only with byte orders for which
+    /// `type_descriptor_with_order` returns `Some`. The default
+    /// implementation calls `write_slice`.
+    fn write_slice_with_order<W: io::Write>(
+        slice: &[Self],
+        writer: W,
+        byte_order: ByteOrder,
+    ) -> Result<(), WriteDataError> {
+        let _ = byte_order;
+        Self::write_slice(slice, writer)
+    }
 }
 
 /// Extension trait for writing `ArrayBase` to `.npy` files.
@@ -97,6 +235,38 @@ pub trait WriteNpyExt {
     /// This function is the Rust equivalent of
     /// [`numpy.save`](https://docs.scipy.org/doc/numpy/reference/generated/numpy.save.html).
     fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError>;
+
+    /// Writes the array to `writer` in `.npy` format with the given options.
+    ///
+    /// The default implementation calls `write_npy` if the options are
+    /// equivalent to the defaults and otherwise returns
+    /// `WriteNpyError::UnsupportedOptions`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray::array;
+    /// use ndarray_npy::dtype::ByteOrder;
+    /// use ndarray_npy::{WriteNpyExt, WriteOptions};
+    /// # use ndarray_npy::WriteNpyError;
+    ///
+    /// let mut npy = Vec::new();
+    /// let options = WriteOptions::new().byte_order(ByteOrder::Little);
+    /// array![1u16, 2, 3].write_npy_with_options(&mut npy, &options)?;
+    /// assert_eq!(&npy[npy.len() - 6..], &[1, 0, 2, 0, 3, 0]);
+    /// # Ok::<_, WriteNpyError>(())
+    /// ```
+    fn write_npy_with_options<W: io::Write>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteNpyError> {
+        if options.is_default() {
+            self.write_npy(writer)
+        } else {
+            Err(WriteNpyError::UnsupportedOptions)
+        }
+    }
 }
 
 impl<A, S, D> WriteNpyExt for ArrayBase<S, D>
@@ -105,15 +275,28 @@ where
     S: Data<Elem = A>,
     D: Dimension,
 {
-    fn write_npy<W: io::Write>(&self, mut writer: W) -> Result<(), WriteNpyError> {
+    fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+        self.write_npy_with_options(writer, &WriteOptions::default())
+    }
+
+    fn write_npy_with_options<W: io::Write>(
+        &self,
+        mut writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteNpyError> {
+        let type_descriptor = options.type_descriptor::<A>()?;
         let write_contiguous = |mut writer: W, fortran_order: bool| {
             Header {
-                type_descriptor: A::type_descriptor(),
+                type_descriptor: type_descriptor.clone(),
                 fortran_order,
                 shape: self.shape().to_owned(),
             }
-            .write(&mut writer)?; // write header
-            A::write_slice(self.as_slice_memory_order().unwrap(), &mut writer)?;
+            .write_with_options(&mut writer, options)?; // write header
+            A::write_slice_with_order(
+                self.as_slice_memory_order().unwrap(),
+                &mut writer,
+                options.byte_order,
+            )?;
             Ok(())
         };
         if self.is_standard_layout() {
@@ -125,49 +308,23 @@ where
         } else {
             // e.g., has custom strides
             Header {
-                type_descriptor: A::type_descriptor(),
+                type_descriptor,
                 fortran_order: false,
                 shape: self.shape().to_owned(),
             }
-            .write(&mut writer)?;
+            .write_with_options(&mut writer, options)?;
             for elem in self.iter() {
-                elem.write(&mut writer)?;
+                A::write_slice_with_order(
+                    std::slice::from_ref(elem),
+                    &mut writer,
+                    options.byte_order,
+                )?;
             }
             Ok(())
         }
     }
 }
 
-/// Write a slice of primitive types to `.npy` files.
-///
-/// # Example
-///
-/// ```no_run
-/// use ndarray_npy::WriteNpyExt;
-/// use std::fs::File;
-/// # use ndarray_npy::WriteNpyError;
-///
-/// let arr: Vec<f64> = vec![1., 2., 3., 4., 5., 6.];
-/// let writer = File::create("vec.npy")?;
-/// arr.write_npy(writer)?;
-/// # Ok::<_, WriteNpyError>(())
-/// ```
-impl<A> WriteNpyExt for [A]
-where
-    A: WritableElement,
-{
-    fn write_npy<W: io::Write>(&self, mut writer: W) -> Result<(), WriteNpyError> {
-        Header {
-            type_descriptor: A::type_descriptor(),
-            fortran_order: false,
-            shape: vec![self.len()],
-        }
-        .write(&mut writer)?;
-        A::write_slice(&self, &mut writer)?;
-        Ok(())
-    }
-}
-
 /// An array element type that can be read from an `.npy` or `.npz` file.
 pub trait ReadableElement: Sized {
     /// Reads to the end of the `reader`, creating a `Vec` of length `len`.
@@ -217,22 +374,37 @@ where
 {
     fn read_npy<R: io::Read>(mut reader: R) -> Result<Self, ReadNpyError> {
         let header = Header::from_reader(&mut reader)?;
-        let shape = header.shape.into_dimension();
-        let ndim = shape.ndim();
-        let len = match shape.size_checked() {
-            Some(len) if len <= std::isize::MAX as usize => len,
-            _ => return Err(ReadNpyError::LengthOverflow),
-        };
-        let data = A::read_to_end_exact_vec(&mut reader, &header.type_descriptor, len)?;
-        ArrayBase::from_shape_vec(shape.set_f(header.fortran_order), data)
-            .unwrap()
-            .into_dimensionality()
-            .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, ndim))
+        read_array_data(header, reader)
     }
 }
 
+/// Reads the data of an array described by `header` from `reader`, which must
+/// be positioned just after the header.
+pub(crate) fn read_array_data<A, S, D, R>(
+    header: Header,
+    mut reader: R,
+) -> Result<ArrayBase<S, D>, ReadNpyError>
+where
+    A: ReadableElement,
+    S: DataOwned<Elem = A>,
+    D: Dimension,
+    R: io::Read,
+{
+    let shape = header.shape.into_dimension();
+    let ndim = shape.ndim();
+    let len = match shape.size_checked() {
+        Some(len) if len <= std::isize::MAX as usize => len,
+        _ => return Err(ReadNpyError::LengthOverflow),
+    };
+    let data = A::read_to_end_exact_vec(&mut reader, &header.type_descriptor, len)?;
+    ArrayBase::from_shape_vec(shape.set_f(header.fortran_order), data)
+        .unwrap()
+        .into_dimensionality()
+        .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, ndim))
+}
+
 macro_rules! impl_writable_primitive {
-    ($elem:ty, $little_desc:expr, $big_desc:expr) => {
+    (@impl $elem:ty, $little_desc:expr, $big_desc:expr, { $($order_methods:tt)* }) => {
         unsafe impl WritableElement for $elem {
             fn type_descriptor() -> PyValue {
                 if cfg!(target_endian = "little") {
@@ -272,8 +444,65 @@ macro_rules! impl_writable_primitive {
                 writer.write_all(cast(slice))?;
                 Ok(())
             }
+
+            $($order_methods)*
         }
     };
+    ($elem:ty, $desc:expr) => {
+        impl_writable_primitive!(@impl $elem, $desc, $desc, {
+            fn type_descriptor_with_order(_byte_order: ByteOrder) -> Option<PyValue> {
+                Some(PyValue::String($desc.into()))
+            }
+        });
+    };
+    ($elem:ty, $little_desc:expr, $big_desc:expr, $write_into:ident) => {
+        impl_writable_primitive!(@impl $elem, $little_desc, $big_desc, {
+            fn type_descriptor_with_order(byte_order: ByteOrder) -> Option<PyValue> {
+                match byte_order {
+                    ByteOrder::Little => Some(PyValue::String($little_desc.into())),
+                    ByteOrder::Big => Some(PyValue::String($big_desc.into())),
+                    ByteOrder::NotApplicable => Some(Self::type_descriptor()),
+                }
+            }
+
+            fn write_slice_with_order<W: io::Write>(
+                slice: &[Self],
+                writer: W,
+                byte_order: ByteOrder,
+            ) -> Result<(), WriteDataError> {
+                match byte_order {
+                    ByteOrder::Little if cfg!(target_endian = "big") => {
+                        write_converted(slice, writer, LittleEndian::$write_into)
+                    }
+                    ByteOrder::Big if cfg!(target_endian = "little") => {
+                        write_converted(slice, writer, BigEndian::$write_into)
+                    }
+                    _ => Self::write_slice(slice, writer),
+                }
+            }
+        });
+    };
+}
+
+/// Writes `slice` to `writer`, converting the elements to bytes with
+/// `convert`. The conversion is performed in chunks to limit the number of
+/// calls to `writer`.
+fn write_converted<T, W>(
+    slice: &[T],
+    mut writer: W,
+    convert: fn(&[T], &mut [u8]),
+) -> Result<(), WriteDataError>
+where
+    W: io::Write,
+{
+    const CHUNK_LEN: usize = 1024;
+    let mut buf = vec![0; slice.len().min(CHUNK_LEN) * mem::size_of::<T>()];
+    for chunk in slice.chunks(CHUNK_LEN) {
+        let bytes = &mut buf[..mem::size_of_val(chunk)];
+        convert(chunk, bytes);
+        writer.write_all(bytes)?;
+    }
+    Ok(())
 }
 
 /// Returns `Ok(_)` iff the `reader` had no more bytes on entry to this
@@ -289,22 +518,52 @@ pub fn check_for_extra_bytes<R: io::Read>(reader: &mut R) -> Result<(), ReadData
     }
 }
 
+/// Reorders the bytes of a Fortran-order array with the given shape and
+/// element size into C order.
+#[cfg(any(feature = "arrow", feature = "safetensors"))]
+pub(crate) fn c_order_bytes(bytes: &[u8], shape: &[usize], itemsize: usize) -> Vec<u8> {
+    // View the data as an array of bytes with an extra last axis for the bytes
+    // of each element, so that iterating in logical order yields C order.
+    let mut dims = shape.to_vec();
+    dims.push(itemsize);
+    let mut strides = Vec::with_capacity(dims.len());
+    let mut stride = itemsize;
+    for &len in shape {
+        strides.push(stride);
+        stride *= len;
+    }
+    strides.push(1);
+    ArrayView::from_shape(IxDyn(&dims).strides(IxDyn(&strides)), bytes)
+        .unwrap()
+        .iter()
+        .copied()
+        .collect()
+}
+
+/// Parses `type_desc` as a scalar type, returning
+/// `ReadDataError::WrongDescriptor` if it is not a valid scalar type.
+pub(crate) fn scalar_type(type_desc: &PyValue) -> Result<ScalarType, ReadDataError> {
+    match DType::from_descr(type_desc) {
+        Ok(DType::Scalar(scalar)) => Ok(scalar),
+        _ => Err(ReadDataError::WrongDescriptor(type_desc.clone())),
+    }
+}
+
 macro_rules! impl_readable_primitive_one_byte {
-    ($elem:ty, [$($desc:expr),*], $zero:expr, $read_into:ident) => {
+    ($elem:ty, $kind:expr, $zero:expr, $read_into:ident) => {
         impl ReadableElement for $elem {
             fn read_to_end_exact_vec<R: io::Read>(
                 mut reader: R,
                 type_desc: &PyValue,
                 len: usize,
             ) -> Result<Vec<Self>, ReadDataError> {
-                match *type_desc {
-                    PyValue::String(ref s) if $(s == $desc)||* => {
-                        let mut out = vec![$zero; len];
-                        reader.$read_into(&mut out)?;
-                        check_for_extra_bytes(&mut reader)?;
-                        Ok(out)
-                    }
-                    ref other => Err(ReadDataError::WrongDescriptor(other.clone())),
+                if scalar_type(type_desc)?.is($kind, 1) {
+                    let mut out = vec![$zero; len];
+                    reader.$read_into(&mut out)?;
+                    check_for_extra_bytes(&mut reader)?;
+                    Ok(out)
+                } else {
+                    Err(ReadDataError::WrongDescriptor(type_desc.clone()))
                 }
             }
         }
@@ -312,33 +571,33 @@ macro_rules! impl_readable_primitive_one_byte {
 }
 
 macro_rules! impl_primitive_one_byte {
-    ($elem:ty, $write_desc:expr, [$($read_desc:expr),*], $zero:expr, $read_into:ident) => {
-        impl_writable_primitive!($elem, $write_desc, $write_desc);
-        impl_readable_primitive_one_byte!($elem, [$($read_desc),*], $zero, $read_into);
+    ($elem:ty, $write_desc:expr, $kind:expr, $zero:expr, $read_into:ident) => {
+        impl_writable_primitive!($elem, $write_desc);
+        impl_readable_primitive_one_byte!($elem, $kind, $zero, $read_into);
     };
 }
 
-impl_primitive_one_byte!(i8, "|i1", ["|i1", "i1", "b"], 0, read_i8_into);
-impl_primitive_one_byte!(u8, "|u1", ["|u1", "u1", "B"], 0, read_exact);
+impl_primitive_one_byte!(i8, "|i1", TypeKind::Int, 0, read_i8_into);
+impl_primitive_one_byte!(u8, "|u1", TypeKind::UInt, 0, read_exact);
 
 macro_rules! impl_readable_primitive_multi_byte {
-    ($elem:ty, $little_desc:expr, $big_desc:expr, $zero:expr, $read_into:ident) => {
+    ($elem:ty, $kind:expr, $zero:expr, $read_into:ident) => {
         impl ReadableElement for $elem {
             fn read_to_end_exact_vec<R: io::Read>(
                 mut reader: R,
                 type_desc: &PyValue,
                 len: usize,
             ) -> Result<Vec<Self>, ReadDataError> {
+                let scalar = scalar_type(type_desc)?;
+                if !scalar.is($kind, mem::size_of::<$elem>()) {
+                    return Err(ReadDataError::WrongDescriptor(type_desc.clone()));
+                }
                 let mut out = vec![$zero; len];
-                match *type_desc {
-                    PyValue::String(ref s) if s == $little_desc => {
-                        reader.$read_into::<LittleEndian>(&mut out)?;
-                    }
-                    PyValue::String(ref s) if s == $big_desc => {
-                        reader.$read_into::<BigEndian>(&mut out)?;
-                    }
-                    ref other => {
-                        return Err(ReadDataError::WrongDescriptor(other.clone()));
+                match scalar.byte_order {
+                    ByteOrder::Little => reader.$read_into::<LittleEndian>(&mut out)?,
+                    ByteOrder::Big => reader.$read_into::<BigEndian>(&mut out)?,
+                    ByteOrder::NotApplicable => {
+                        return Err(ReadDataError::WrongDescriptor(type_desc.clone()));
                     }
                 }
                 check_for_extra_bytes(&mut reader)?;
@@ -349,22 +608,94 @@ macro_rules! impl_readable_primitive_multi_byte {
 }
 
 macro_rules! impl_primitive_multi_byte {
-    ($elem:ty, $little_desc:expr, $big_desc:expr, $zero:expr, $read_into:ident) => {
-        impl_writable_primitive!($elem, $little_desc, $big_desc);
-        impl_readable_primitive_multi_byte!($elem, $little_desc, $big_desc, $zero, $read_into);
+    (
+        $elem:ty,
+        $little_desc:expr,
+        $big_desc:expr,
+        $kind:expr,
+        $zero:expr,
+        $read_into:ident,
+        $write_into:ident
+    ) => {
+        impl_writable_primitive!($elem, $little_desc, $big_desc, $write_into);
+        impl_readable_primitive_multi_byte!($elem, $kind, $zero, $read_into);
     };
 }
 
-impl_primitive_multi_byte!(i16, "<i2", ">i2", 0, read_i16_into);
-impl_primitive_multi_byte!(i32, "<i4", ">i4", 0, read_i32_into);
-impl_primitive_multi_byte!(i64, "<i8", ">i8", 0, read_i64_into);
+impl_primitive_multi_byte!(
+    i16,
+    "<i2",
+    ">i2",
+    TypeKind::Int,
+    0,
+    read_i16_into,
+    write_i16_into
+);
+impl_primitive_multi_byte!(
+    i32,
+    "<i4",
+    ">i4",
+    TypeKind::Int,
+    0,
+    read_i32_into,
+    write_i32_into
+);
+impl_primitive_multi_byte!(
+    i64,
+    "<i8",
+    ">i8",
+    TypeKind::Int,
+    0,
+    read_i64_into,
+    write_i64_into
+);
 
-impl_primitive_multi_byte!(u16, "<u2", ">u2", 0, read_u16_into);
-impl_primitive_multi_byte!(u32, "<u4", ">u4", 0, read_u32_into);
-impl_primitive_multi_byte!(u64, "<u8", ">u8", 0, read_u64_into);
+impl_primitive_multi_byte!(
+    u16,
+    "<u2",
+    ">u2",
+    TypeKind::UInt,
+    0,
+    read_u16_into,
+    write_u16_into
+);
+impl_primitive_multi_byte!(
+    u32,
+    "<u4",
+    ">u4",
+    TypeKind::UInt,
+    0,
+    read_u32_into,
+    write_u32_into
+);
+impl_primitive_multi_byte!(
+    u64,
+    "<u8",
+    ">u8",
+    TypeKind::UInt,
+    0,
+    read_u64_into,
+    write_u64_into
+);
 
-impl_primitive_multi_byte!(f32, "<f4", ">f4", 0., read_f32_into);
-impl_primitive_multi_byte!(f64, "<f8", ">f8", 0., read_f64_into);
+impl_primitive_multi_byte!(
+    f32,
+    "<f4",
+    ">f4",
+    TypeKind::Float,
+    0.,
+    read_f32_into,
+    write_f32_into
+);
+impl_primitive_multi_byte!(
+    f64,
+    "<f8",
+    ">f8",
+    TypeKind::Float,
+    0.,
+    read_f64_into,
+    write_f64_into
+);
 
 impl ReadableElement for bool {
     fn read_to_end_exact_vec<R: io::Read>(
@@ -372,8 +703,8 @@ impl ReadableElement for bool {
         type_desc: &PyValue,
         len: usize,
     ) -> Result<Vec<Self>, ReadDataError> {
-        match *type_desc {
-            PyValue::String(ref s) if s == "|b1" => {
+        match scalar_type(type_desc)? {
+            ref scalar if scalar.is(TypeKind::Bool, 1) => {
                 // Read the data.
                 let mut bytes: Vec<u8> = vec![0; len];
                 reader.read_exact(&mut bytes)?;
@@ -409,7 +740,7 @@ impl ReadableElement for bool {
                     Ok(unsafe { Vec::from_raw_parts(ptr.cast::<bool>(), len, cap) })
                 }
             }
-            ref other => Err(ReadDataError::WrongDescriptor(other.clone())),
+            _ => Err(ReadDataError::WrongDescriptor(type_desc.clone())),
         }
     }
 }
@@ -417,14 +748,34 @@ impl ReadableElement for bool {
 // Rust guarantees that `bool` is one byte, the bitwise representation of
 // `false` is `0x00`, and the bitwise representation of `true` is `0x01`, so we
 // can just cast the data in-place.
-impl_writable_primitive!(bool, "|b1", "|b1");
+impl_writable_primitive!(bool, "|b1");
 
 #[cfg(test)]
 mod test {
-    use super::ReadableElement;
+    use super::{read_scalar, ReadNpyError, ReadableElement, WriteNpyExt};
+    use ndarray::{arr0, array};
     use py_literal::Value as PyValue;
     use std::io::Cursor;
 
+    #[test]
+    fn read_scalars() {
+        let mut npy = Vec::new();
+        arr0(1.5f32).write_npy(&mut npy).unwrap();
+        assert_eq!(read_scalar::<_, f32>(&npy[..]).unwrap(), 1.5);
+
+        let mut npy = Vec::new();
+        array![7u64].write_npy(&mut npy).unwrap();
+        assert_eq!(read_scalar::<_, u64>(&npy[..]).unwrap(), 7);
+        assert!(read_scalar::<_, i32>(&npy[..]).is_err());
+
+        let mut npy = Vec::new();
+        array![[7u64]].write_npy(&mut npy).unwrap();
+        match read_scalar::<_, u64>(&npy[..]) {
+            Err(ReadNpyError::WrongShape(shape)) => assert_eq!(shape, [1, 1]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn read_bool() {
         let data = &[0x00, 0x01, 0x00, 0x00, 0x01];
@@ -0,0 +1,177 @@
+//! Reading and writing `nalgebra` matrices.
+//!
+//! Matrices are stored in column-major order, so they are written with
+//! `fortran_order: True` and the data is copied as-is. Column vectors with a
+//! static number of columns (e.g. `DVector` and `Vector3`) are written as 1-D
+//! arrays; all other matrices are written as 2-D arrays. When reading, a 1-D
+//! array becomes a row vector if the matrix type has a static number of rows
+//! equal to 1 (e.g. `RowDVector`), and a column vector otherwise.
+
+use super::{ReadNpyError, ReadNpyExt, ReadableElement, WritableElement, WriteNpyError};
+use super::{WriteNpyExt, WriteOptions};
+use crate::Header;
+use nalgebra::allocator::Allocator;
+use nalgebra::{DefaultAllocator, Dim, Matrix, OMatrix, RawStorage, Scalar};
+use std::io;
+
+impl<T, R, C, S> WriteNpyExt for Matrix<T, R, C, S>
+where
+    T: Scalar + WritableElement,
+    R: Dim,
+    C: Dim,
+    S: RawStorage<T, R, C>,
+{
+    fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+        self.write_npy_with_options(writer, &WriteOptions::default())
+    }
+
+    fn write_npy_with_options<W: io::Write>(
+        &self,
+        mut writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteNpyError> {
+        let (nrows, ncols) = self.shape();
+        let shape = if C::try_to_usize() == Some(1) {
+            vec![nrows]
+        } else {
+            vec![nrows, ncols]
+        };
+        Header {
+            type_descriptor: options.type_descriptor::<T>()?,
+            fortran_order: true,
+            shape,
+        }
+        .write_with_options(&mut writer, options)?;
+
+        let (row_stride, col_stride) = self.strides();
+        if (nrows <= 1 || row_stride == 1) && (ncols <= 1 || col_stride == nrows) {
+            // This is safe because the strides show that the `nrows * ncols`
+            // elements are contiguous in column-major order.
+            let data = unsafe { std::slice::from_raw_parts(self.data.ptr(), nrows * ncols) };
+            T::write_slice_with_order(data, &mut writer, options.byte_order)?;
+        } else {
+            // e.g., a view with custom strides
+            for elem in self.iter() {
+                T::write_slice_with_order(
+                    std::slice::from_ref(elem),
+                    &mut writer,
+                    options.byte_order,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, R, C> ReadNpyExt for OMatrix<T, R, C>
+where
+    T: Scalar + ReadableElement,
+    R: Dim,
+    C: Dim,
+    DefaultAllocator: Allocator<R, C>,
+{
+    fn read_npy<Rd: io::Read>(mut reader: Rd) -> Result<Self, ReadNpyError> {
+        let header = Header::from_reader(&mut reader)?;
+        let (nrows, ncols) = match *header.shape.as_slice() {
+            [nrows, ncols] => (nrows, ncols),
+            // A 1-D array is read as a row vector if the number of rows is
+            // statically 1, and as a column vector otherwise.
+            [len] if R::try_to_usize() == Some(1) && C::try_to_usize() != Some(1) => (1, len),
+            [len] => (len, 1),
+            _ => return Err(ReadNpyError::WrongNdim(Some(2), header.shape.len())),
+        };
+        let fits = |dim: Option<usize>, len: usize| dim.map_or(true, |dim| dim == len);
+        if !fits(R::try_to_usize(), nrows) || !fits(C::try_to_usize(), ncols) {
+            return Err(ReadNpyError::WrongShape(header.shape));
+        }
+        let len = nrows
+            .checked_mul(ncols)
+            .filter(|&len| len <= std::isize::MAX as usize)
+            .ok_or(ReadNpyError::LengthOverflow)?;
+        let data = T::read_to_end_exact_vec(&mut reader, &header.type_descriptor, len)?;
+        let (rows, cols) = (R::from_usize(nrows), C::from_usize(ncols));
+        if header.fortran_order || header.shape.len() == 1 {
+            Ok(OMatrix::from_vec_generic(rows, cols, data))
+        } else {
+            Ok(OMatrix::from_row_iterator_generic(rows, cols, data))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ReadNpyError, ReadNpyExt, WriteNpyExt};
+    use nalgebra::{DMatrix, DVector, Matrix2x3, RowDVector, RowVector3, Vector3};
+    use ndarray::{array, Array1, Array2};
+
+    #[test]
+    fn write_column_major() {
+        let m = Matrix2x3::new(1., 2., 3., 4., 5., 6.);
+        let mut npy = Vec::new();
+        m.write_npy(&mut npy).unwrap();
+        let header = crate::Header::from_reader(&npy[..]).unwrap();
+        assert!(header.fortran_order);
+        assert_eq!(header.shape, [2, 3]);
+        let arr = Array2::<f64>::read_npy(&npy[..]).unwrap();
+        assert_eq!(arr, array![[1., 2., 3.], [4., 5., 6.]]);
+        assert_eq!(Matrix2x3::<f64>::read_npy(&npy[..]).unwrap(), m);
+
+        // A view with a row stride other than 1.
+        let big = DMatrix::from_fn(4, 5, |i, j| (i * 10 + j) as i32);
+        let view = big.view_with_steps((0, 1), (2, 2), (1, 2));
+        let mut npy = Vec::new();
+        view.write_npy(&mut npy).unwrap();
+        let arr = Array2::<i32>::read_npy(&npy[..]).unwrap();
+        assert_eq!(arr, array![[1, 4], [21, 24]]);
+    }
+
+    #[test]
+    fn vectors() {
+        let v = DVector::from_vec(vec![1u16, 2, 3]);
+        let mut npy = Vec::new();
+        v.write_npy(&mut npy).unwrap();
+        assert_eq!(Array1::<u16>::read_npy(&npy[..]).unwrap(), array![1, 2, 3]);
+        assert_eq!(DVector::<u16>::read_npy(&npy[..]).unwrap(), v);
+        assert_eq!(
+            Vector3::<u16>::read_npy(&npy[..]).unwrap(),
+            Vector3::new(1, 2, 3)
+        );
+
+        let mut npy = Vec::new();
+        RowDVector::from_vec(vec![1u16, 2, 3])
+            .write_npy(&mut npy)
+            .unwrap();
+        assert_eq!(
+            Array2::<u16>::read_npy(&npy[..]).unwrap(),
+            array![[1, 2, 3]]
+        );
+
+        // A 1-D array can be read as a row vector.
+        let mut npy = Vec::new();
+        array![1u16, 2, 3].write_npy(&mut npy).unwrap();
+        assert_eq!(
+            RowDVector::<u16>::read_npy(&npy[..]).unwrap(),
+            RowDVector::from_vec(vec![1, 2, 3])
+        );
+        assert_eq!(
+            RowVector3::<u16>::read_npy(&npy[..]).unwrap(),
+            RowVector3::new(1, 2, 3)
+        );
+        assert_eq!(
+            DVector::<u16>::read_npy(&npy[..]).unwrap(),
+            DVector::from_vec(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn read_c_order() {
+        let mut npy = Vec::new();
+        array![[1i64, 2, 3], [4, 5, 6]].write_npy(&mut npy).unwrap();
+        let m = DMatrix::<i64>::read_npy(&npy[..]).unwrap();
+        assert_eq!(m, DMatrix::from_row_slice(2, 3, &[1, 2, 3, 4, 5, 6]));
+        match Vector3::<i64>::read_npy(&npy[..]) {
+            Err(ReadNpyError::WrongShape(shape)) => assert_eq!(shape, [2, 3]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
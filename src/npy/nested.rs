@@ -0,0 +1,271 @@
+use super::{WritableElement, WriteDataError, WriteNpyError, WriteNpyExt, WriteOptions};
+use crate::dtype::ByteOrder;
+use crate::Header;
+use std::io;
+
+/// An element, or a possibly nested collection of elements, that can be
+/// written as (part of) an N-D array.
+///
+/// This is implemented for all [`WritableElement`] types, and for `Vec<T>`
+/// and `[T; N]` (with `N` up to 32) where `T: NestedElements`. It allows slices and collections
+/// such as `Vec<Vec<f64>>`, `&[[i32; 3]]` and `Vec<[u8; 4]>` to be written
+/// with [`WriteNpyExt`], each level of nesting adding an axis to the array.
+///
+/// [`WritableElement`]: trait.WritableElement.html
+/// [`WriteNpyExt`]: trait.WriteNpyExt.html
+pub trait NestedElements {
+    /// The type of the innermost elements.
+    type Elem: WritableElement;
+
+    /// Appends the lengths of this collection and its first nested
+    /// collections to `shape`, i.e. the shape of this part of the array if it
+    /// is rectangular.
+    fn push_shape(&self, shape: &mut Vec<usize>);
+
+    /// Appends the lengths of an empty collection of this type to `shape`.
+    ///
+    /// Axes whose lengths are unknown have length zero.
+    fn push_empty_shape(shape: &mut Vec<usize>);
+
+    /// Returns `true` if this part of the array has the given shape.
+    fn has_shape(&self, shape: &[usize]) -> bool;
+
+    /// Writes the elements of `slice` in standard (row-major) order.
+    fn write_elements<W: io::Write>(
+        slice: &[Self],
+        writer: &mut W,
+        byte_order: ByteOrder,
+    ) -> Result<(), WriteDataError>
+    where
+        Self: Sized;
+}
+
+impl<A: WritableElement> NestedElements for A {
+    type Elem = A;
+
+    fn push_shape(&self, _shape: &mut Vec<usize>) {}
+
+    fn push_empty_shape(_shape: &mut Vec<usize>) {}
+
+    fn has_shape(&self, shape: &[usize]) -> bool {
+        shape.is_empty()
+    }
+
+    fn write_elements<W: io::Write>(
+        slice: &[Self],
+        writer: &mut W,
+        byte_order: ByteOrder,
+    ) -> Result<(), WriteDataError> {
+        A::write_slice_with_order(slice, writer, byte_order)
+    }
+}
+
+/// Implements `NestedElements` for a collection type that can be indexed as
+/// `[T]`, where an empty collection has length `$empty_len`.
+macro_rules! impl_nested_collection {
+    ([$($generics:tt)*] $collection:ty, $empty_len:expr) => {
+        impl<T: NestedElements, $($generics)*> NestedElements for $collection {
+            type Elem = T::Elem;
+
+            fn push_shape(&self, shape: &mut Vec<usize>) {
+                push_slice_shape(&self[..], shape)
+            }
+
+            fn push_empty_shape(shape: &mut Vec<usize>) {
+                shape.push($empty_len);
+                T::push_empty_shape(shape);
+            }
+
+            fn has_shape(&self, shape: &[usize]) -> bool {
+                slice_has_shape(&self[..], shape)
+            }
+
+            fn write_elements<W: io::Write>(
+                slice: &[Self],
+                writer: &mut W,
+                byte_order: ByteOrder,
+            ) -> Result<(), WriteDataError> {
+                for collection in slice {
+                    T::write_elements(&collection[..], writer, byte_order)?;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_nested_collection!([] Vec<T>, 0);
+
+fn push_slice_shape<T: NestedElements>(slice: &[T], shape: &mut Vec<usize>) {
+    match slice.first() {
+        Some(first) => {
+            shape.push(slice.len());
+            first.push_shape(shape);
+        }
+        None => {
+            shape.push(0);
+            T::push_empty_shape(shape);
+        }
+    }
+}
+
+fn slice_has_shape<T: NestedElements>(slice: &[T], shape: &[usize]) -> bool {
+    match shape.split_first() {
+        Some((&len, inner)) => slice.len() == len && slice.iter().all(|t| t.has_shape(inner)),
+        None => false,
+    }
+}
+
+/// Writes a slice of elements or nested collections to `.npy` files.
+///
+/// Nested collections must be rectangular, i.e. all the collections at each
+/// level of nesting must have the same length; otherwise,
+/// `WriteNpyError::NotRectangular` is returned.
+///
+/// # Example
+///
+/// ```no_run
+/// use ndarray_npy::WriteNpyExt;
+/// use std::fs::File;
+/// # use ndarray_npy::WriteNpyError;
+///
+/// let arr: Vec<f64> = vec![1., 2., 3., 4., 5., 6.];
+/// let writer = File::create("vec.npy")?;
+/// arr.write_npy(writer)?;
+///
+/// let arr: Vec<Vec<i32>> = vec![vec![1, 2, 3], vec![4, 5, 6]];
+/// let writer = File::create("vec2.npy")?;
+/// arr.write_npy(writer)?; // written with shape [2, 3]
+/// # Ok::<_, WriteNpyError>(())
+/// ```
+impl<T> WriteNpyExt for [T]
+where
+    T: NestedElements,
+{
+    fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+        self.write_npy_with_options(writer, &WriteOptions::default())
+    }
+
+    fn write_npy_with_options<W: io::Write>(
+        &self,
+        mut writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteNpyError> {
+        let mut shape = Vec::new();
+        push_slice_shape(self, &mut shape);
+        if !slice_has_shape(self, &shape) {
+            return Err(WriteNpyError::NotRectangular);
+        }
+        Header {
+            type_descriptor: options.type_descriptor::<T::Elem>()?,
+            fortran_order: false,
+            shape,
+        }
+        .write_with_options(&mut writer, options)?;
+        T::write_elements(self, &mut writer, options.byte_order)?;
+        Ok(())
+    }
+}
+
+impl<T> WriteNpyExt for Vec<T>
+where
+    T: NestedElements,
+{
+    fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+        self.write_npy_with_options(writer, &WriteOptions::default())
+    }
+
+    fn write_npy_with_options<W: io::Write>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+    ) -> Result<(), WriteNpyError> {
+        self[..].write_npy_with_options(writer, options)
+    }
+}
+
+/// Implements `NestedElements` and `WriteNpyExt` for arrays of each of the
+/// given lengths.
+macro_rules! impl_nested_array {
+    ($($len:expr),*) => {
+        $(
+            impl_nested_collection!([] [T; $len], $len);
+
+            impl<T> WriteNpyExt for [T; $len]
+            where
+                T: NestedElements,
+            {
+                fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+                    self.write_npy_with_options(writer, &WriteOptions::default())
+                }
+
+                fn write_npy_with_options<W: io::Write>(
+                    &self,
+                    writer: W,
+                    options: &WriteOptions,
+                ) -> Result<(), WriteNpyError> {
+                    self[..].write_npy_with_options(writer, options)
+                }
+            }
+        )*
+    };
+}
+
+impl_nested_array!(
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32
+);
+
+#[cfg(test)]
+mod test {
+    use crate::{to_npy_bytes, ReadNpyExt, WriteNpyError};
+    use ndarray::{array, Array1, Array2, Array3};
+
+    #[test]
+    fn nested_shapes() {
+        let vv = vec![vec![1i32, 2, 3], vec![4, 5, 6]];
+        let arr = Array2::<i32>::read_npy(&to_npy_bytes(&vv).unwrap()[..]).unwrap();
+        assert_eq!(arr, array![[1, 2, 3], [4, 5, 6]]);
+
+        let va: Vec<[f64; 2]> = vec![[1., 2.], [3., 4.], [5., 6.]];
+        let arr = Array2::<f64>::read_npy(&to_npy_bytes(&va[..]).unwrap()[..]).unwrap();
+        assert_eq!(arr, array![[1., 2.], [3., 4.], [5., 6.]]);
+
+        let aav = [[vec![1u8], vec![2]], [vec![3], vec![4]]];
+        let arr = Array3::<u8>::read_npy(&to_npy_bytes(&aav).unwrap()[..]).unwrap();
+        assert_eq!(arr, array![[[1], [2]], [[3], [4]]]);
+
+        let flat = [true, false];
+        let arr = Array1::<bool>::read_npy(&to_npy_bytes(&flat).unwrap()[..]).unwrap();
+        assert_eq!(arr, array![true, false]);
+    }
+
+    #[test]
+    fn empty_shapes() {
+        let empty: Vec<[i16; 3]> = Vec::new();
+        let arr = Array2::<i16>::read_npy(&to_npy_bytes(&empty).unwrap()[..]).unwrap();
+        assert_eq!(arr.shape(), [0, 3]);
+
+        let empty: Vec<Vec<i16>> = vec![Vec::new(), Vec::new()];
+        let arr = Array2::<i16>::read_npy(&to_npy_bytes(&empty).unwrap()[..]).unwrap();
+        assert_eq!(arr.shape(), [2, 0]);
+
+        let empty: Vec<[[u8; 2]; 0]> = vec![[]];
+        let arr = Array3::<u8>::read_npy(&to_npy_bytes(&empty).unwrap()[..]).unwrap();
+        assert_eq!(arr.shape(), [1, 0, 2]);
+    }
+
+    #[test]
+    fn ragged() {
+        let ragged = vec![vec![1i32, 2], vec![3]];
+        match to_npy_bytes(&ragged) {
+            Err(WriteNpyError::NotRectangular) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let ragged = vec![[vec![1i32], vec![2]], [vec![3], vec![]]];
+        match to_npy_bytes(&ragged) {
+            Err(WriteNpyError::NotRectangular) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use super::{error::WriteNpyError, header::Version, WritableElement};
+use crate::dtype::ByteOrder;
+use py_literal::Value as PyValue;
+
+/// Options for writing `.npy` files.
+///
+/// The options are set with builder-style methods, starting from
+/// [`new`](#method.new), which is equivalent to the default options used by
+/// [`WriteNpyExt::write_npy`](trait.WriteNpyExt.html#method.write_npy).
+///
+/// # Example
+///
+/// ```
+/// use ndarray_npy::dtype::ByteOrder;
+/// use ndarray_npy::WriteOptions;
+///
+/// let options = WriteOptions::new().byte_order(ByteOrder::Big);
+/// ```
+#[derive(Clone, Debug)]
+pub struct WriteOptions {
+    pub(crate) byte_order: ByteOrder,
+    pub(crate) version: Option<Version>,
+    pub(crate) header_padding: usize,
+}
+
+impl WriteOptions {
+    /// Creates the default options, which write the data in the native byte
+    /// order with the oldest format version that can represent the header.
+    pub fn new() -> WriteOptions {
+        WriteOptions {
+            byte_order: ByteOrder::NATIVE,
+            version: None,
+            header_padding: 0,
+        }
+    }
+
+    /// Sets the byte order of the written data. Elements are byte-swapped as
+    /// they are written if this differs from the native byte order.
+    ///
+    /// `ByteOrder::NotApplicable` is treated as the native byte order.
+    pub fn byte_order(mut self, byte_order: ByteOrder) -> WriteOptions {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// Sets the format version of the header. Writing fails with
+    /// `FormatHeaderError::HeaderTooLong` if the header does not fit in this
+    /// version, instead of falling back to a newer one.
+    pub fn version(mut self, version: Version) -> WriteOptions {
+        self.version = Some(version);
+        self
+    }
+
+    /// Reserves at least `bytes` bytes of additional padding in the header,
+    /// so that it can later be overwritten in place with a longer one, e.g.
+    /// with a larger shape, using [`Header::rewrite`].
+    ///
+    /// The total header length is still rounded up to a multiple of 64 bytes.
+    ///
+    /// [`Header::rewrite`]: struct.Header.html#method.rewrite
+    pub fn header_padding(mut self, bytes: usize) -> WriteOptions {
+        self.header_padding = bytes;
+        self
+    }
+
+    /// Returns `true` if the options are equivalent to the defaults.
+    pub(crate) fn is_default(&self) -> bool {
+        (self.byte_order == ByteOrder::NATIVE || self.byte_order == ByteOrder::NotApplicable)
+            && self.version.is_none()
+            && self.header_padding == 0
+    }
+
+    /// Returns the type descriptor of `A` in the selected byte order.
+    pub(crate) fn type_descriptor<A: WritableElement>(&self) -> Result<PyValue, WriteNpyError> {
+        A::type_descriptor_with_order(self.byte_order)
+            .ok_or(WriteNpyError::UnsupportedByteOrder(self.byte_order))
+    }
+}
+
+impl Default for WriteOptions {
+    fn default() -> WriteOptions {
+        WriteOptions::new()
+    }
+}
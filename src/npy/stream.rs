@@ -1,18 +1,25 @@
 use super::{
-    error::{WriteDataError, WriteNpyError},
-    header::Header,
+    atomic,
+    error::{CloseStreamError, WriteDataError, WriteNpyError},
+    header::{Header, Version},
+    options::WriteOptions,
     WritableElement,
 };
-use ndarray::{Dimension, IntoDimension};
+use crate::dtype::ByteOrder;
+use ndarray::{ArrayBase, Data, Dimension, IntoDimension};
 use std::{
-    fs::File,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
     marker,
     path::{Path, PathBuf},
 };
 
+/// Default capacity of the write buffer of an [`NpyOutStream`] in bytes.
+const DEFAULT_BUFFER_CAPACITY: usize = 64 * 1024;
+
 /// This define a stream that allows progressively output a stream of array data
 /// into a `.npy` file.
-/// 
+///
 /// # Example
 ///
 /// ```
@@ -21,7 +28,7 @@ use std::{
 ///
 /// let mut stream = NpyOutStreamBuilder::<f32>::new("out.npy").for_arr2([2, 2]).build()?;
 /// let data = vec![0_f32; 4];
-/// 
+///
 /// let ret = stream.write_slice(&data)?;
 /// assert_eq!(ret, 4);
 /// # Ok::<_, WriteNpyError>(())
@@ -29,11 +36,43 @@ use std::{
 pub struct NpyOutStream<T: WritableElement> {
     tot_elems: usize,     // total number of elements to output
     written_elems: usize, // how many elements have been written
-    writer: File,
+    shape: Vec<usize>,
+    fortran_order: bool,
+    writer: BufWriter<File>,
+    path: PathBuf,
+    temp_path: Option<PathBuf>, // temporary file in atomic mode
+    byte_order: ByteOrder,
+    sync: bool,
+    drop_policy: DropPolicy,
     closed: bool,
     _marker: marker::PhantomData<T>,
 }
 
+/// What to do with the file of an [`NpyOutStream`] that receives fewer elements than given by
+/// its shape.
+///
+/// The policy applies when such a stream is dropped without being closed, and (except for
+/// `Panic`) when it is closed. In atomic mode, the temporary file is always removed, so the
+/// policy only decides whether dropping the stream panics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Leave the partial file as it is. This is the default.
+    Keep,
+    /// Panic when the stream is dropped without being closed, unless the thread is already
+    /// panicking. The partial file is left as it is.
+    Panic,
+    /// Truncate the file to zero length, so that it cannot be mistaken for a valid `.npy` file.
+    Truncate,
+    /// Delete the file.
+    Delete,
+}
+
+impl Default for DropPolicy {
+    fn default() -> DropPolicy {
+        DropPolicy::Keep
+    }
+}
+
 /// This is the builder for creating an output stream that write a NPY array into
 /// a file.
 ///
@@ -51,6 +90,11 @@ pub struct NpyOutStream<T: WritableElement> {
 pub struct NpyOutStreamBuilder<T: WritableElement> {
     path: PathBuf,
     header: Header,
+    options: WriteOptions,
+    atomic: bool,
+    sync: bool,
+    drop_policy: DropPolicy,
+    buffer_capacity: usize,
     _marker: marker::PhantomData<T>,
 }
 
@@ -66,12 +110,174 @@ impl<T: WritableElement> NpyOutStream<T> {
                     .into(),
             )
         } else {
-            T::write_slice(slice, &mut self.writer)?;
+            T::write_slice_with_order(slice, &mut self.writer, self.byte_order)?;
             self.written_elems += slice.len();
             Ok(self.written_elems)
         }
     }
 
+    /// Output a single element to the stream.
+    ///
+    /// Like [`write_slice`](#method.write_slice), this returns the number of elements written
+    /// so far, and raises an error if the stream has already received all expected elements.
+    pub fn write(&mut self, elem: &T) -> Result<usize, WriteNpyError> {
+        self.write_slice(std::slice::from_ref(elem))
+    }
+
+    /// Output an array to the stream, such as one slab of a larger array.
+    ///
+    /// For a C-order stream, the shape of `array` must equal the trailing dimensions of the
+    /// stream's shape, e.g. a `(4, 5)` array can be written to a `(3, 4, 5)` stream once per
+    /// index of the first axis. For a Fortran-order stream, it must equal the leading
+    /// dimensions instead. The array is written at a boundary between such slabs, and may be
+    /// any view, including a non-contiguous one. Returns the number of elements written so far.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray::{s, Array3};
+    /// use ndarray_npy::NpyOutStreamBuilder;
+    /// # use ndarray_npy::WriteNpyError;
+    ///
+    /// let data = Array3::<f64>::zeros((4, 5, 3));
+    /// let mut stream = NpyOutStreamBuilder::<f64>::new("out.npy").for_arr3([3, 4, 5]).build()?;
+    /// for t in 0..3 {
+    ///     stream.write_array(&data.slice(s![.., .., t]))?;
+    /// }
+    /// stream.close()?;
+    /// # Ok::<_, WriteNpyError>(())
+    /// ```
+    pub fn write_array<S, D>(&mut self, array: &ArrayBase<S, D>) -> Result<usize, WriteNpyError>
+    where
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        self.check_slab(array.shape())?;
+        let len = array.len();
+        if self.written_elems + len > self.tot_elems {
+            return Err(
+                WriteDataError::TooManyElements(self.tot_elems, self.written_elems + len).into(),
+            );
+        }
+
+        // Reversing the axes of a Fortran-order slab gives its elements in C order.
+        let view = if self.fortran_order {
+            array.view().reversed_axes()
+        } else {
+            array.view()
+        };
+        if let Some(slice) = view.as_slice() {
+            T::write_slice_with_order(slice, &mut self.writer, self.byte_order)?;
+        } else {
+            for elem in view.iter() {
+                T::write_slice_with_order(
+                    std::slice::from_ref(elem),
+                    &mut self.writer,
+                    self.byte_order,
+                )?;
+            }
+        }
+        self.written_elems += len;
+        Ok(self.written_elems)
+    }
+
+    /// Return the number of elements in each lane of the stream: the length of the first axis
+    /// for a Fortran-order stream, or of the last axis for a C-order stream.
+    ///
+    /// A lane is a contiguous run of elements in the file, e.g. a column of a Fortran-order
+    /// matrix or a row of a C-order matrix.
+    pub fn lane_len(&self) -> usize {
+        let lane_len = if self.fortran_order {
+            self.shape.first()
+        } else {
+            self.shape.last()
+        };
+        lane_len.copied().unwrap_or(1)
+    }
+
+    /// Output one lane of the array, e.g. one column of a Fortran-order matrix. See
+    /// [`lane_len`](#method.lane_len).
+    ///
+    /// An error is raised if the length of `lane` differs from the lane length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray_npy::NpyOutStreamBuilder;
+    /// # use ndarray_npy::WriteNpyError;
+    ///
+    /// // A 3x2 matrix in column-major order, written column by column.
+    /// let mut stream = NpyOutStreamBuilder::<f32>::new("out.npy").for_arr2([3, 2]).f().build()?;
+    /// assert_eq!(stream.lane_len(), 3);
+    /// stream.write_lane(&[1., 2., 3.])?;
+    /// stream.write_lane(&[4., 5., 6.])?;
+    /// stream.close()?;
+    /// # Ok::<_, WriteNpyError>(())
+    /// ```
+    pub fn write_lane(&mut self, lane: &[T]) -> Result<usize, WriteNpyError> {
+        if self.shape.is_empty() {
+            self.check_slab(&[])?;
+        } else {
+            self.check_slab(&[lane.len()])?;
+        }
+        self.write_slice(lane)
+    }
+
+    /// Output one slab of the array along its outermost axis in file order: the sub-array for
+    /// one index of the last axis for a Fortran-order stream, or of the first axis for a
+    /// C-order stream. The slab may be any view.
+    ///
+    /// An error is raised if the slab's shape differs from the remaining dimensions of the
+    /// stream's shape.
+    pub fn write_slab<S, D>(&mut self, slab: &ArrayBase<S, D>) -> Result<usize, WriteNpyError>
+    where
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        if slab.ndim() + 1 != self.shape.len() {
+            let expected = match self.shape.len() {
+                0 => vec![],
+                ndim if self.fortran_order => self.shape[..ndim - 1].to_vec(),
+                _ => self.shape[1..].to_vec(),
+            };
+            return Err(WriteDataError::ShapeMismatch {
+                expected,
+                found: slab.shape().to_vec(),
+            }
+            .into());
+        }
+        self.write_array(slab)
+    }
+
+    /// Check that an array of the given shape can be written as the next slab of the stream.
+    fn check_slab(&self, shape: &[usize]) -> Result<(), WriteDataError> {
+        let ndim = self.shape.len();
+        let expected = match shape.len() {
+            k if k > ndim => &self.shape[..],
+            k if self.fortran_order => &self.shape[..k],
+            k => &self.shape[ndim - k..],
+        };
+        if expected != shape {
+            return Err(WriteDataError::ShapeMismatch {
+                expected: expected.to_vec(),
+                found: shape.to_vec(),
+            });
+        }
+        let len: usize = shape.iter().product();
+        if len != 0 && self.written_elems % len != 0 {
+            return Err(WriteDataError::Unaligned {
+                offset: self.written_elems,
+                len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Flush the buffered data to the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
     /// Return the total number of elements expected to be put into the stream.
     #[inline(always)]
     pub fn tot_elems(&self) -> usize {
@@ -84,27 +290,87 @@ impl<T: WritableElement> NpyOutStream<T> {
         self.tot_elems == self.written_elems
     }
 
-    /// Close the stream. If the written elements is fewer than the expected total number
-    /// of elements. A [`WriteDataError`](WriteDataError) will arise.
-    pub fn close(mut self) -> Result<(), WriteDataError> {
+    /// Close the stream, flushing the written data and, if requested with
+    /// [`NpyOutStreamBuilder::sync`], syncing it to disk.
+    ///
+    /// If fewer elements than expected have been written, the [`DropPolicy`] is applied to the
+    /// partial file and [`CloseStreamError::TooFewElements`] is returned, including any error
+    /// from applying the policy.
+    ///
+    /// In atomic mode, the temporary file is synced to disk and renamed to the destination
+    /// path only if all elements have been written; otherwise it is removed.
+    pub fn close(mut self) -> Result<(), CloseStreamError> {
         self.closed = true;
 
         if self.written_elems < self.tot_elems {
-            Err(WriteDataError::TooFewElements(
-                self.tot_elems(),
-                self.written_elems,
-            ))
-        } else {
-            Ok(())
+            return Err(CloseStreamError::TooFewElements {
+                expected: self.tot_elems,
+                written: self.written_elems,
+                cleanup: self.discard().err(),
+            });
+        }
+        let result = self.persist();
+        if result.is_err() {
+            self.remove_temp();
+        }
+        Ok(result?)
+    }
+
+    /// Flush the data and sync it to disk if requested. In atomic mode, sync the temporary file
+    /// and rename it to the destination path.
+    fn persist(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        if let Some(temp_path) = &self.temp_path {
+            atomic::persist(self.writer.get_ref(), temp_path, &self.path)?;
+            self.temp_path = None;
+        } else if self.sync {
+            self.writer.get_ref().sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Discard the partial file according to the drop policy. In atomic mode, only remove the
+    /// temporary file.
+    fn discard(&mut self) -> io::Result<()> {
+        if let Some(temp_path) = self.temp_path.take() {
+            return fs::remove_file(temp_path);
+        }
+        match self.drop_policy {
+            DropPolicy::Keep | DropPolicy::Panic => Ok(()),
+            DropPolicy::Truncate => {
+                // Flush first so that no buffered data is written after truncating.
+                self.writer.flush()?;
+                self.writer.get_ref().set_len(0)
+            }
+            DropPolicy::Delete => fs::remove_file(&self.path),
+        }
+    }
+
+    /// Remove the temporary file, if any, without renaming it to the destination.
+    fn remove_temp(&mut self) {
+        if let Some(temp_path) = self.temp_path.take() {
+            let _ = fs::remove_file(temp_path);
         }
     }
 }
 
 impl<T: WritableElement> Drop for NpyOutStream<T> {
     fn drop(&mut self) {
-        if !self.closed && !self.finished() {
-            eprintln!("WARNING: The NpyOutStream is closed without receiving all elements: expect {} elements, received {} elements",
-                      self.tot_elems(), self.written_elems);
+        if self.closed {
+            return;
+        }
+        if self.finished() {
+            // A stream in atomic mode is only persisted by `close`.
+            let _ = self.writer.flush();
+            self.remove_temp();
+        } else {
+            let _ = self.discard();
+            if self.drop_policy == DropPolicy::Panic && !std::thread::panicking() {
+                panic!(
+                    "NpyOutStream dropped without receiving all elements: expected {} elements, received {} elements",
+                    self.tot_elems, self.written_elems
+                );
+            }
         }
     }
 }
@@ -119,6 +385,11 @@ impl<T: WritableElement> NpyOutStreamBuilder<T> {
                 fortran_order: false,
                 shape: Vec::with_capacity(3),
             },
+            options: WriteOptions::new(),
+            atomic: false,
+            sync: false,
+            drop_policy: DropPolicy::Keep,
+            buffer_capacity: DEFAULT_BUFFER_CAPACITY,
             _marker: marker::PhantomData,
         }
     }
@@ -164,16 +435,86 @@ impl<T: WritableElement> NpyOutStreamBuilder<T> {
         self
     }
 
+    /// Set the byte order of the output data. The default is the native byte order.
+    pub fn byte_order(mut self, byte_order: ByteOrder) -> NpyOutStreamBuilder<T> {
+        self.options = self.options.byte_order(byte_order);
+        self
+    }
+
+    /// Set the format version of the `.npy` header. By default, the oldest version that can
+    /// represent the header is used.
+    pub fn version(mut self, version: Version) -> NpyOutStreamBuilder<T> {
+        self.options = self.options.version(version);
+        self
+    }
+
+    /// Reserve at least `bytes` bytes of additional padding in the header, so that it can later
+    /// be rewritten in place with [`Header::rewrite`](crate::Header::rewrite).
+    pub fn header_padding(mut self, bytes: usize) -> NpyOutStreamBuilder<T> {
+        self.options = self.options.header_padding(bytes);
+        self
+    }
+
+    /// Set whether to write the file atomically. In atomic mode, the data is written to a
+    /// temporary file in the same directory, which is renamed to the destination path only on a
+    /// successful [`NpyOutStream::close`], so an interrupted stream never leaves a truncated
+    /// file behind.
+    pub fn atomic(mut self, atomic: bool) -> NpyOutStreamBuilder<T> {
+        self.atomic = atomic;
+        self
+    }
+
+    /// Set whether [`NpyOutStream::close`] syncs the file to disk. Atomic streams are always
+    /// synced before being renamed.
+    pub fn sync(mut self, sync: bool) -> NpyOutStreamBuilder<T> {
+        self.sync = sync;
+        self
+    }
+
+    /// Set what to do with the file if the stream receives fewer elements than expected. The
+    /// default is [`DropPolicy::Keep`].
+    pub fn drop_policy(mut self, drop_policy: DropPolicy) -> NpyOutStreamBuilder<T> {
+        self.drop_policy = drop_policy;
+        self
+    }
+
+    /// Set the capacity of the stream's write buffer in bytes, which defaults to 64 KiB. Small
+    /// writes are collected in the buffer and written to the file together; writes larger than
+    /// the buffer go directly to the file. A capacity of zero disables buffering.
+    pub fn buffer_capacity(mut self, capacity: usize) -> NpyOutStreamBuilder<T> {
+        self.buffer_capacity = capacity;
+        self
+    }
+
     /// Consume the current builder, and produce a [`NpyOutStream`] ready to use.
-    pub fn build(self) -> Result<NpyOutStream<T>, WriteNpyError> {
-        let mut writer = File::create(self.path)?;
-        self.header.write(&mut writer)?;
+    pub fn build(mut self) -> Result<NpyOutStream<T>, WriteNpyError> {
+        self.header.type_descriptor = self.options.type_descriptor::<T>()?;
+        let (file, temp_path) = if self.atomic {
+            let (file, temp_path) = atomic::create_temp(&self.path)?;
+            (file, Some(temp_path))
+        } else {
+            (File::create(&self.path)?, None)
+        };
+        let mut writer = BufWriter::with_capacity(self.buffer_capacity, file);
+        if let Err(err) = self.header.write_with_options(&mut writer, &self.options) {
+            if let Some(temp_path) = temp_path {
+                let _ = fs::remove_file(temp_path);
+            }
+            return Err(err.into());
+        }
 
         let tot_elems = self.header.shape.iter().product();
         Ok(NpyOutStream {
             tot_elems,
             written_elems: 0,
+            shape: self.header.shape,
+            fortran_order: self.header.fortran_order,
             writer,
+            path: self.path,
+            temp_path,
+            byte_order: self.options.byte_order,
+            sync: self.sync,
+            drop_policy: self.drop_policy,
             closed: false,
             _marker: marker::PhantomData,
         })
@@ -182,7 +523,34 @@ impl<T: WritableElement> NpyOutStreamBuilder<T> {
 
 #[cfg(test)]
 mod test {
-    use super::NpyOutStreamBuilder;
+    use super::{DropPolicy, NpyOutStreamBuilder};
+    use crate::dtype::ByteOrder;
+    use crate::{CloseStreamError, NpyLaneReader, WriteDataError, WriteNpyError};
+    use ndarray::{array, s, Array1, Array2, Array3};
+    use std::path::{Path, PathBuf};
+
+    /// A temporary directory for a test, which is removed when dropped (even
+    /// if the test panics).
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let path =
+                std::env::temp_dir().join(format!("ndarray-npy-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
 
     #[test]
     fn test_2x3() {
@@ -215,6 +583,231 @@ mod test {
         assert!(stream.finished());
     }
 
+    #[test]
+    fn test_big_endian() {
+        let dir = TempDir::new("big-endian");
+        let path = dir.path().join("out.npy");
+        let mut stream = NpyOutStreamBuilder::<u32>::new(&path)
+            .for_arr1(3)
+            .byte_order(ByteOrder::Big)
+            .build()
+            .unwrap();
+        stream.write_slice(&[1, 2, 0x01020304]).unwrap();
+        stream.close().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[bytes.len() - 4..], &[1, 2, 3, 4]);
+        let arr: Array1<u32> = crate::read_npy(&path).unwrap();
+        assert_eq!(arr, array![1, 2, 0x01020304]);
+    }
+
+    #[test]
+    fn test_atomic() {
+        let dir = TempDir::new("atomic");
+        let path = dir.path().join("out.npy");
+        let build = || {
+            NpyOutStreamBuilder::<f64>::new(&path)
+                .for_arr1(2)
+                .atomic(true)
+                .build()
+                .unwrap()
+        };
+
+        // An incomplete stream leaves nothing behind.
+        let mut stream = build();
+        stream.write_slice(&[1.]).unwrap();
+        assert!(!path.exists());
+        assert!(stream.close().is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        // A complete stream is only visible once closed.
+        let mut stream = build();
+        stream.write_slice(&[1., 2.]).unwrap();
+        assert!(!path.exists());
+        stream.close().unwrap();
+        let arr: Array1<f64> = crate::read_npy(&path).unwrap();
+        assert_eq!(arr, array![1., 2.]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // Dropping a stream without closing it keeps the existing file.
+        let mut stream = build();
+        stream.write_slice(&[3., 4.]).unwrap();
+        drop(stream);
+        let arr: Array1<f64> = crate::read_npy(&path).unwrap();
+        assert_eq!(arr, array![1., 2.]);
+    }
+
+    #[test]
+    fn test_drop_policy() {
+        let dir = TempDir::new("drop-policy");
+        let path = dir.path().join("out.npy");
+        let build = |drop_policy| {
+            let mut stream = NpyOutStreamBuilder::<i32>::new(&path)
+                .for_arr1(3)
+                .drop_policy(drop_policy)
+                .build()
+                .unwrap();
+            stream.write_slice(&[1, 2]).unwrap();
+            stream
+        };
+
+        drop(build(DropPolicy::Keep));
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        drop(build(DropPolicy::Truncate));
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+        drop(build(DropPolicy::Delete));
+        assert!(!path.exists());
+
+        match build(DropPolicy::Delete).close() {
+            Err(CloseStreamError::TooFewElements {
+                expected: 3,
+                written: 2,
+                cleanup: None,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(!path.exists());
+
+        let mut stream = NpyOutStreamBuilder::<i32>::new(&path)
+            .for_arr1(3)
+            .sync(true)
+            .drop_policy(DropPolicy::Delete)
+            .build()
+            .unwrap();
+        stream.write_slice(&[1, 2, 3]).unwrap();
+        stream.close().unwrap();
+        let arr: Array1<i32> = crate::read_npy(&path).unwrap();
+        assert_eq!(arr, array![1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "without receiving all elements")]
+    fn test_drop_policy_panic() {
+        let dir = TempDir::new("drop-policy-panic");
+        let mut stream = NpyOutStreamBuilder::<u8>::new(dir.path().join("out.npy"))
+            .for_arr1(2)
+            .drop_policy(DropPolicy::Panic)
+            .build()
+            .unwrap();
+        stream.write_slice(&[1]).unwrap();
+    }
+
+    #[test]
+    fn test_buffered() {
+        let dir = TempDir::new("buffered");
+        let path = dir.path().join("out.npy");
+        for &capacity in &[0, 5, 1024] {
+            let mut stream = NpyOutStreamBuilder::<u16>::new(&path)
+                .for_arr2([10, 10])
+                .buffer_capacity(capacity)
+                .build()
+                .unwrap();
+            for i in 0..50 {
+                assert_eq!(stream.write(&i).unwrap(), i as usize + 1);
+            }
+            stream.write_slice(&(50..100).collect::<Vec<_>>()).unwrap();
+            assert!(stream.write(&100).is_err());
+            stream.close().unwrap();
+            let arr: Array2<u16> = crate::read_npy(&path).unwrap();
+            assert_eq!(
+                arr,
+                Array2::from_shape_fn((10, 10), |(i, j)| (i * 10 + j) as u16)
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_array() {
+        let dir = TempDir::new("write-array");
+        let path = dir.path().join("out.npy");
+        let data = Array3::from_shape_fn((3, 4, 5), |(i, j, k)| (i * 100 + j * 10 + k) as i32);
+        for &fortran_order in &[false, true] {
+            let builder = NpyOutStreamBuilder::<i32>::new(&path).for_arr3([3, 4, 5]);
+            let mut stream = if fortran_order {
+                builder.f().build().unwrap()
+            } else {
+                builder.build().unwrap()
+            };
+            if fortran_order {
+                // Fortran-order slabs span the leading axes.
+                for k in 0..5 {
+                    stream.write_array(&data.slice(s![.., .., k])).unwrap();
+                }
+            } else {
+                // Write a slab from a non-contiguous view, then rows.
+                let transposed = data.slice(s![0, .., ..]).reversed_axes().to_owned();
+                stream.write_array(&transposed.t()).unwrap();
+                for slab in data.slice(s![1.., .., ..]).outer_iter() {
+                    for row in slab.outer_iter() {
+                        stream.write_array(&row).unwrap();
+                    }
+                }
+            }
+            assert!(stream.finished());
+            stream.close().unwrap();
+            let arr: Array3<i32> = crate::read_npy(&path).unwrap();
+            assert_eq!(arr, data);
+        }
+    }
+
+    #[test]
+    fn test_write_array_errors() {
+        let dir = TempDir::new("write-array-errors");
+        let mut stream = NpyOutStreamBuilder::<i32>::new(dir.path().join("out.npy"))
+            .for_arr2([2, 3])
+            .build()
+            .unwrap();
+        match stream.write_array(&array![1, 2]) {
+            Err(WriteNpyError::WriteData(WriteDataError::ShapeMismatch { expected, found })) => {
+                assert_eq!(expected, [3]);
+                assert_eq!(found, [2]);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        stream.write(&0).unwrap();
+        match stream.write_array(&array![1, 2, 3]) {
+            Err(WriteNpyError::WriteData(WriteDataError::Unaligned { offset: 1, len: 3 })) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        stream.write_slice(&[1, 2]).unwrap();
+        stream.write_array(&array![3, 4, 5]).unwrap();
+        stream.close().unwrap();
+    }
+
+    #[test]
+    fn test_fortran_lanes() {
+        let data = Array3::from_shape_fn((2, 3, 4), |(i, j, k)| (i * 100 + j * 10 + k) as i64);
+        let dir = TempDir::new("fortran-lanes");
+        let path = dir.path().join("out.npy");
+        let mut stream = NpyOutStreamBuilder::<i64>::new(&path)
+            .for_arr3([2, 3, 4])
+            .f()
+            .build()
+            .unwrap();
+        assert_eq!(stream.lane_len(), 2);
+        assert!(stream.write_lane(&[0, 1, 2]).is_err());
+        assert!(stream.write_slab(&data.slice(s![.., 0, ..])).is_err());
+        stream.write_slab(&data.slice(s![.., .., 0])).unwrap();
+        for k in 1..4 {
+            for j in 0..3 {
+                let lane = data.slice(s![.., j, k]).to_vec();
+                stream.write_lane(&lane).unwrap();
+            }
+        }
+        stream.close().unwrap();
+        let arr: Array3<i64> = crate::read_npy(&path).unwrap();
+        assert_eq!(arr, data);
+
+        let file = std::fs::File::open(&path).unwrap();
+        let reader = NpyLaneReader::<_, i64>::new(file).unwrap();
+        assert_eq!(reader.lane_len(), 2);
+        assert_eq!(reader.num_lanes(), 12);
+        let lanes: Vec<Array1<i64>> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(lanes.len(), 12);
+        for (n, lane) in lanes.iter().enumerate() {
+            assert_eq!(lane, &data.slice(s![.., n % 3, n / 3]));
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_panic() {
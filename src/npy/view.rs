@@ -0,0 +1,287 @@
+use super::{scalar_type, ReadNpyError, ReadNpyExt, ReadableElement, WriteNpyError, WriteNpyExt};
+use super::{ViewDataError, ViewNpyError};
+use crate::dtype::{ByteOrder, TypeKind};
+use crate::Header;
+use ndarray::prelude::*;
+use ndarray::{CowArray, IntoDimension};
+use py_literal::Value as PyValue;
+use std::mem;
+
+/// An array element type that can be viewed in place in the data of an
+/// `.npy` file, without copying.
+///
+/// # Safety
+///
+/// Implementors must ensure that any bytes accepted by `check_descriptor`
+/// and `check_data` are a valid representation of a slice of `Self`.
+pub unsafe trait ViewElement: Sized {
+    /// Checks that `type_desc` describes `Self` in the native byte order.
+    fn check_descriptor(type_desc: &PyValue) -> Result<(), ViewDataError>;
+
+    /// Checks that all the elements in `bytes` are valid values of `Self`.
+    ///
+    /// The default implementation accepts any bytes, which is correct for
+    /// types where every bit pattern is valid.
+    fn check_data(_bytes: &[u8]) -> Result<(), ViewDataError> {
+        Ok(())
+    }
+}
+
+/// Checks that `type_desc` is a scalar type of the given kind and size, in the
+/// native byte order if the size is greater than one byte.
+fn check_scalar<A>(type_desc: &PyValue, kind: TypeKind) -> Result<(), ViewDataError> {
+    let scalar = match scalar_type(type_desc) {
+        Ok(scalar) if scalar.is(kind, mem::size_of::<A>()) => scalar,
+        _ => return Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+    };
+    match scalar.byte_order {
+        ByteOrder::NotApplicable => Ok(()),
+        order if order == ByteOrder::NATIVE => Ok(()),
+        _ => Err(ViewDataError::NonNativeEndian),
+    }
+}
+
+macro_rules! impl_view_primitive {
+    ($($elem:ty => $kind:expr),*) => {
+        $(
+            unsafe impl ViewElement for $elem {
+                fn check_descriptor(type_desc: &PyValue) -> Result<(), ViewDataError> {
+                    check_scalar::<$elem>(type_desc, $kind)
+                }
+            }
+        )*
+    };
+}
+
+impl_view_primitive!(
+    i8 => TypeKind::Int,
+    i16 => TypeKind::Int,
+    i32 => TypeKind::Int,
+    i64 => TypeKind::Int,
+    u8 => TypeKind::UInt,
+    u16 => TypeKind::UInt,
+    u32 => TypeKind::UInt,
+    u64 => TypeKind::UInt,
+    f32 => TypeKind::Float,
+    f64 => TypeKind::Float
+);
+
+unsafe impl ViewElement for bool {
+    fn check_descriptor(type_desc: &PyValue) -> Result<(), ViewDataError> {
+        check_scalar::<bool>(type_desc, TypeKind::Bool)
+    }
+
+    fn check_data(bytes: &[u8]) -> Result<(), ViewDataError> {
+        // Creating a `bool` with a value other than `0x00` or `0x01` is
+        // undefined behavior.
+        match bytes.iter().find(|&&byte| byte > 1) {
+            Some(&byte) => Err(ViewDataError::ParseBoolError(byte)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Views the data of an in-memory `.npy` file in place, without copying.
+///
+/// This requires the data to be in the native byte order and aligned for the
+/// element type, which is the case for `.npy` files written on the same
+/// platform when `bytes` itself is suitably aligned (e.g. the start of an
+/// allocation). Use [`from_npy_bytes`] to fall back to copying otherwise.
+///
+/// [`from_npy_bytes`]: fn.from_npy_bytes.html
+///
+/// # Example
+///
+/// ```
+/// use ndarray::{array, ArrayView2};
+/// use ndarray_npy::{to_npy_bytes, view_npy};
+///
+/// let bytes = to_npy_bytes(&array![[1u8, 2], [3, 4]])?;
+/// let view: ArrayView2<u8> = view_npy(&bytes)?;
+/// assert_eq!(view, array![[1, 2], [3, 4]]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn view_npy<A, D>(bytes: &[u8]) -> Result<ArrayView<'_, A, D>, ViewNpyError>
+where
+    A: ViewElement,
+    D: Dimension,
+{
+    let mut data = bytes;
+    let header = Header::from_reader(&mut data)?;
+    A::check_descriptor(&header.type_descriptor)?;
+    let shape = header.shape.into_dimension();
+    let ndim = shape.ndim();
+    let len = match shape.size_checked() {
+        Some(len) if len <= std::isize::MAX as usize => len,
+        _ => return Err(ViewNpyError::LengthOverflow),
+    };
+    let num_bytes = len
+        .checked_mul(mem::size_of::<A>())
+        .ok_or(ViewNpyError::LengthOverflow)?;
+    if data.len() < num_bytes {
+        return Err(ViewDataError::MissingData.into());
+    } else if data.len() > num_bytes {
+        return Err(ViewDataError::ExtraBytes(data.len() - num_bytes).into());
+    }
+    if data.as_ptr() as usize % mem::align_of::<A>() != 0 {
+        return Err(ViewDataError::Misaligned {
+            align: mem::align_of::<A>(),
+        }
+        .into());
+    }
+    A::check_data(data)?;
+    // This is safe because:
+    //
+    // * The pointer is aligned for `A`. (See the check above.)
+    //
+    // * `data` contains exactly `len` elements of `A`, which are all valid
+    //   values. (See the checks above and the safety requirements of
+    //   `ViewElement`.)
+    //
+    // * The lifetime of the new slice is tied to `bytes`.
+    let elems: &[A] = unsafe { std::slice::from_raw_parts(data.as_ptr().cast::<A>(), len) };
+    ArrayView::from_shape(shape.set_f(header.fortran_order), elems)
+        .unwrap()
+        .into_dimensionality()
+        .map_err(|_| ViewNpyError::WrongNdim(D::NDIM, ndim))
+}
+
+/// Reads an array from an in-memory `.npy` file.
+///
+/// The data is viewed in place without copying if possible (see
+/// [`view_npy`]), and is otherwise read into a new array, e.g. if it is
+/// misaligned or in the non-native byte order. This is convenient for
+/// payloads received as `Vec<u8>` or `bytes::Bytes`.
+///
+/// [`view_npy`]: fn.view_npy.html
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{from_npy_bytes, to_npy_bytes};
+///
+/// let bytes = to_npy_bytes(&array![[1., 2.], [3., 4.]])?;
+/// let arr = from_npy_bytes::<f64, ndarray::Ix2>(&bytes)?;
+/// assert_eq!(arr, array![[1., 2.], [3., 4.]]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn from_npy_bytes<A, D>(bytes: &[u8]) -> Result<CowArray<'_, A, D>, ReadNpyError>
+where
+    A: ReadableElement + ViewElement,
+    D: Dimension,
+{
+    match view_npy(bytes) {
+        Ok(view) => Ok(view.into()),
+        // Reading reports the error if the data cannot be read either.
+        Err(_) => Ok(Array::read_npy(bytes)?.into()),
+    }
+}
+
+/// Writes an array or slice in `.npy` format to a new `Vec<u8>`.
+pub fn to_npy_bytes<T>(array: &T) -> Result<Vec<u8>, WriteNpyError>
+where
+    T: WriteNpyExt + ?Sized,
+{
+    let mut bytes = Vec::new();
+    array.write_npy(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_npy_bytes, to_npy_bytes, view_npy};
+    use crate::{ViewDataError, ViewNpyError, WriteNpyExt, WriteOptions};
+    use ndarray::{array, Array2, ArrayView1, ArrayView3, Ix3};
+
+    /// Copies `bytes` into a buffer aligned to 8 bytes, starting at `offset`.
+    fn aligned(bytes: &[u8], offset: usize) -> (Vec<u64>, usize) {
+        let mut storage = vec![0u64; (bytes.len() + offset + 7) / 8];
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), storage.len() * 8)
+        };
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+        (storage, bytes.len())
+    }
+
+    fn as_bytes(storage: &[u64], offset: usize, len: usize) -> &[u8] {
+        let buf =
+            unsafe { std::slice::from_raw_parts(storage.as_ptr().cast::<u8>(), storage.len() * 8) };
+        &buf[offset..offset + len]
+    }
+
+    #[test]
+    fn view_in_place() {
+        let arr = array![[[1i32, 2], [3, 4]], [[5, 6], [7, 8]]];
+        let fortran = arr.t();
+        for original in &[arr.view(), fortran] {
+            let (storage, len) = aligned(&to_npy_bytes(original).unwrap(), 0);
+            let bytes = as_bytes(&storage, 0, len);
+            let view: ArrayView3<i32> = view_npy(bytes).unwrap();
+            assert_eq!(&view, original);
+            let start = bytes.as_ptr() as usize;
+            assert!((start..start + bytes.len()).contains(&(view.as_ptr() as usize)));
+            let cow = from_npy_bytes::<i32, Ix3>(bytes).unwrap();
+            assert!(cow.is_view());
+            assert_eq!(&cow, original);
+        }
+    }
+
+    #[test]
+    fn copy_when_misaligned() {
+        let arr = array![[1.5f64, -2.], [3., 4.25]];
+        let (storage, len) = aligned(&to_npy_bytes(&arr).unwrap(), 1);
+        let bytes = as_bytes(&storage, 1, len);
+        match view_npy::<f64, ndarray::Ix2>(bytes) {
+            Err(ViewNpyError::ViewData(ViewDataError::Misaligned { align: 8 })) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let cow = from_npy_bytes::<f64, ndarray::Ix2>(bytes).unwrap();
+        assert!(cow.is_owned());
+        assert_eq!(cow, arr);
+    }
+
+    #[test]
+    fn copy_when_non_native() {
+        let arr = array![[1u32, 2], [3, 4]];
+        let order = if cfg!(target_endian = "little") {
+            crate::dtype::ByteOrder::Big
+        } else {
+            crate::dtype::ByteOrder::Little
+        };
+        let mut buf = Vec::new();
+        arr.write_npy_with_options(&mut buf, &WriteOptions::new().byte_order(order))
+            .unwrap();
+        let (storage, len) = aligned(&buf, 0);
+        let bytes = as_bytes(&storage, 0, len);
+        match view_npy::<u32, ndarray::Ix2>(bytes) {
+            Err(ViewNpyError::ViewData(ViewDataError::NonNativeEndian)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let read: Array2<u32> = from_npy_bytes(bytes).unwrap().into_owned();
+        assert_eq!(read, arr);
+    }
+
+    #[test]
+    fn view_errors() {
+        let mut bytes = to_npy_bytes(&[true, false][..]).unwrap();
+        let view: ArrayView1<bool> = view_npy(&bytes).unwrap();
+        assert_eq!(view, array![true, false]);
+
+        *bytes.last_mut().unwrap() = 2;
+        match view_npy::<bool, ndarray::Ix1>(&bytes) {
+            Err(ViewNpyError::ViewData(ViewDataError::ParseBoolError(2))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match view_npy::<u8, ndarray::Ix1>(&bytes) {
+            Err(ViewNpyError::ViewData(ViewDataError::WrongDescriptor(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        bytes.pop();
+        match view_npy::<bool, ndarray::Ix1>(&bytes) {
+            Err(ViewNpyError::ViewData(ViewDataError::MissingData)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(from_npy_bytes::<bool, ndarray::Ix1>(&bytes).is_err());
+    }
+}
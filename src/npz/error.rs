@@ -12,6 +12,32 @@ pub enum WriteNpzError {
     /// An error caused by writing an inner `.npy` file.
     #[error("cannot write npy file to npz archive")]
     Npy(#[from] WriteNpyError),
+
+    /// The size of the `.npy` file for the array overflows `usize`.
+    #[error("npy file is too large to add to npz archive")]
+    EntryTooLarge,
+
+    /// The components of a sparse matrix are inconsistent.
+    #[error("invalid sparse matrix: {0}")]
+    InvalidSparse(String),
+
+    /// An error writing the array with the given name.
+    #[error("cannot write array {name:?} to npz archive")]
+    Entry {
+        name: String,
+        #[source]
+        source: Box<WriteNpzError>,
+    },
+}
+
+impl WriteNpzError {
+    /// Wraps `err` with the name of the array being written.
+    pub(crate) fn entry(name: impl Into<String>, err: impl Into<WriteNpzError>) -> Self {
+        WriteNpzError::Entry {
+            name: name.into(),
+            source: Box::new(err.into()),
+        }
+    }
 }
 
 /// An error reading a `.npz` file.
@@ -24,4 +50,30 @@ pub enum ReadNpzError {
     /// An error caused by reading an inner `.npy` file.
     #[error("cannot read npy file in npz archive")]
     Npy(#[from] ReadNpyError),
+
+    /// An error reading the array with the given name.
+    #[error("cannot read array {name:?} from npz archive")]
+    Entry {
+        name: String,
+        #[source]
+        source: Box<ReadNpzError>,
+    },
+
+    /// The `format` entry of a sparse matrix file is not a supported format.
+    #[error("unsupported sparse matrix format: {0:?}")]
+    SparseFormat(String),
+
+    /// The components of a sparse matrix are inconsistent.
+    #[error("invalid sparse matrix: {0}")]
+    InvalidSparse(String),
+}
+
+impl ReadNpzError {
+    /// Wraps `err` with the name of the array being read.
+    pub(crate) fn entry(name: impl Into<String>, err: impl Into<ReadNpzError>) -> Self {
+        ReadNpzError::Entry {
+            name: name.into(),
+            source: Box::new(err.into()),
+        }
+    }
 }
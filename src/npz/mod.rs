@@ -1,16 +1,49 @@
 mod error;
+mod sparse;
 pub use error::*;
+pub use sparse::{CooMatrix, CsMatrix, SparseMatrix};
 
-use crate::{ReadNpyExt, ReadableElement, WritableElement, WriteNpyExt};
+use crate::npy::read_scalar;
+use crate::{
+    Header, ReadNpyError, ReadNpyExt, ReadableElement, WritableElement, WriteNpyError, WriteNpyExt,
+};
 use ndarray::prelude::*;
 use ndarray::{Data, DataOwned};
-use std::io::{Read, Seek, Write};
+use std::io::{Cursor, Read, Seek, Write};
+use std::mem;
 use zip::result::ZipError;
 use zip::write::FileOptions;
-use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use zip::{ZipArchive, ZipWriter};
+
+/// The compression method of an entry in an `.npz` file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompressionMethod {
+    /// The entry is not compressed, as written by `numpy.savez`.
+    Stored,
+    /// The entry is compressed with Deflate, as written by
+    /// `numpy.savez_compressed`.
+    Deflated,
+    /// Any other compression method.
+    Other,
+}
+
+impl CompressionMethod {
+    fn from_zip(method: zip::CompressionMethod) -> Self {
+        if method == zip::CompressionMethod::STORE {
+            CompressionMethod::Stored
+        } else if method == zip::CompressionMethod::DEFLATE {
+            CompressionMethod::Deflated
+        } else {
+            CompressionMethod::Other
+        }
+    }
+}
 
 /// Writer for `.npz` files.
 ///
+/// Entries of 4 GiB or more are automatically written with Zip64 extensions,
+/// as is the central directory of archives that need them.
+///
 /// # Example
 ///
 /// ```no_run
@@ -23,6 +56,7 @@ use zip::{CompressionMethod, ZipArchive, ZipWriter};
 /// let b: Array1<i32> = array![7, 8, 9];
 /// npz.add_array("a", &a)?;
 /// npz.add_array("b", &b)?;
+/// npz.finish()?;
 /// # Ok::<_, Box<dyn std::error::Error>>(())
 /// ```
 pub struct NpzWriter<W: Write + Seek> {
@@ -37,7 +71,7 @@ impl<W: Write + Seek> NpzWriter<W> {
     pub fn new(writer: W) -> NpzWriter<W> {
         NpzWriter {
             zip: ZipWriter::new(writer),
-            options: FileOptions::default().compression_method(CompressionMethod::Stored),
+            options: FileOptions::default().compression_method(zip::CompressionMethod::Stored),
         }
     }
 
@@ -48,7 +82,7 @@ impl<W: Write + Seek> NpzWriter<W> {
     pub fn new_compressed(writer: W) -> NpzWriter<W> {
         NpzWriter {
             zip: ZipWriter::new(writer),
-            options: FileOptions::default().compression_method(CompressionMethod::Deflated),
+            options: FileOptions::default().compression_method(zip::CompressionMethod::Deflated),
         }
     }
 
@@ -64,14 +98,124 @@ impl<W: Write + Seek> NpzWriter<W> {
         S: Data,
         D: Dimension,
     {
-        self.zip.start_file(name, self.options)?;
+        // Zip64 extensions are required for entries of 4 GiB or more.
+        let header_len = Header {
+            type_descriptor: S::Elem::type_descriptor(),
+            fortran_order: false,
+            shape: array.shape().to_owned(),
+        }
+        .to_bytes()
+        .map_err(WriteNpyError::from)?
+        .len();
+        let npy_len = array
+            .len()
+            .checked_mul(mem::size_of::<S::Elem>())
+            .and_then(|data_len| data_len.checked_add(header_len))
+            .ok_or(WriteNpzError::EntryTooLarge)?;
+        let large_file = npy_len as u64 > u64::from(std::u32::MAX);
+        self.zip
+            .start_file(name, self.options.large_file(large_file))?;
         array.write_npy(&mut self.zip)?;
         Ok(())
     }
+
+    /// Adds the contents of a complete `.npy` file with the specified `name`.
+    #[cfg(feature = "safetensors")]
+    pub(crate) fn add_npy_bytes(&mut self, name: &str, npy: &[u8]) -> Result<(), WriteNpzError> {
+        let large_file = npy.len() as u64 > u64::from(std::u32::MAX);
+        self.zip
+            .start_file(name, self.options.large_file(large_file))?;
+        self.zip.write_all(npy).map_err(WriteNpyError::from)?;
+        Ok(())
+    }
+
+    /// Finishes writing the `.npz` file and returns the underlying writer.
+    ///
+    /// This writes the zip central directory. If the `NpzWriter` is dropped
+    /// without calling `finish`, the central directory is written on drop
+    /// and any error is silently ignored, so you should call this method to
+    /// ensure that the file is valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray::array;
+    /// use ndarray_npy::NpzWriter;
+    /// use std::io::Cursor;
+    ///
+    /// let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    /// npz.add_array("a", &array![1, 2, 3])?;
+    /// let bytes: Vec<u8> = npz.finish()?.into_inner();
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn finish(mut self) -> Result<W, WriteNpzError> {
+        Ok(self.zip.finish()?)
+    }
+
+    /// Adds all of the arrays yielded by `arrays` to the `.npz` file.
+    ///
+    /// The arrays are added in iteration order, so passing a reference to a
+    /// `BTreeMap<String, Array<A, D>>` writes them sorted by name. If adding
+    /// an array fails, the error identifies the name of the array.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ndarray::{array, ArrayD};
+    /// use ndarray_npy::NpzWriter;
+    /// use std::collections::BTreeMap;
+    /// use std::fs::File;
+    ///
+    /// let mut arrays = BTreeMap::<String, ArrayD<f64>>::new();
+    /// arrays.insert("a".into(), array![[1., 2.], [3., 4.]].into_dyn());
+    /// arrays.insert("b".into(), array![5., 6., 7.].into_dyn());
+    /// let mut npz = NpzWriter::new(File::create("arrays.npz")?);
+    /// npz.write_all(&arrays)?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn write_all<'a, I, N, S, D>(&mut self, arrays: I) -> Result<(), WriteNpzError>
+    where
+        I: IntoIterator<Item = (N, &'a ArrayBase<S, D>)>,
+        N: Into<String>,
+        S::Elem: WritableElement,
+        S: Data + 'a,
+        D: Dimension + 'a,
+    {
+        for (name, array) in arrays {
+            let name = name.into();
+            if let Err(err) = self.add_array(name.clone(), array) {
+                return Err(WriteNpzError::entry(name, err));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Metadata describing an array in an `.npz` file.
+///
+/// This is returned by [`NpzReader::entries`](struct.NpzReader.html#method.entries).
+#[derive(Clone, Debug)]
+pub struct NpzEntry {
+    /// The name of the array in the archive.
+    pub name: String,
+    /// The compression method of the entry.
+    pub compression: CompressionMethod,
+    /// The compressed size of the entry in bytes.
+    pub compressed_size: u64,
+    /// The uncompressed size of the entry (the size of the `.npy` file) in
+    /// bytes.
+    pub size: u64,
+    /// The header of the inner `.npy` file.
+    pub header: Header,
 }
 
 /// Reader for `.npz` files.
 ///
+/// Archives using Zip64 extensions, such as those written by [`numpy.savez`]
+/// for large arrays, are supported.
+///
+/// [`numpy.savez`]: https://docs.scipy.org/doc/numpy/reference/generated/numpy.savez.html
+///
 /// # Example
 ///
 /// ```no_run
@@ -113,6 +257,50 @@ impl<R: Read + Seek> NpzReader<R> {
             .collect::<Result<_, ZipError>>()?)
     }
 
+    /// Returns the name of the entry at `index`, without decompressing it.
+    fn name_by_index(&mut self, index: usize) -> Result<String, ReadNpzError> {
+        Ok(self.zip.by_index_raw(index)?.name().to_owned())
+    }
+
+    /// Returns metadata for all of the arrays in the file, in archive order.
+    ///
+    /// Only the header of each inner `.npy` file is decompressed and parsed;
+    /// the array data is not read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ndarray_npy::NpzReader;
+    /// use std::fs::File;
+    ///
+    /// let mut npz = NpzReader::new(File::open("arrays.npz")?)?;
+    /// for entry in npz.entries()? {
+    ///     println!("{}: {} {:?}", entry.name, entry.header.type_descriptor, entry.header.shape);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn entries(&mut self) -> Result<Vec<NpzEntry>, ReadNpzError> {
+        (0..self.zip.len())
+            .map(|index| {
+                let name = self.name_by_index(index)?;
+                let mut file = self
+                    .zip
+                    .by_index(index)
+                    .map_err(|err| ReadNpzError::entry(name.clone(), err))?;
+                match Header::from_reader(&mut file) {
+                    Ok(header) => Ok(NpzEntry {
+                        compression: CompressionMethod::from_zip(file.compression()),
+                        compressed_size: file.compressed_size(),
+                        size: file.size(),
+                        header,
+                        name,
+                    }),
+                    Err(err) => Err(ReadNpzError::entry(name, ReadNpyError::from(err))),
+                }
+            })
+            .collect()
+    }
+
     /// Reads an array by name.
     pub fn by_name<S, D>(&mut self, name: &str) -> Result<ArrayBase<S, D>, ReadNpzError>
     where
@@ -123,6 +311,28 @@ impl<R: Read + Seek> NpzReader<R> {
         Ok(ArrayBase::<S, D>::read_npy(self.zip.by_name(name)?)?)
     }
 
+    /// Reads a scalar by name.
+    ///
+    /// The array must have shape `()` or `(1,)`, as is the case for scalars
+    /// such as hyperparameters saved with `numpy.savez`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ndarray_npy::NpzReader;
+    /// use std::fs::File;
+    ///
+    /// let mut npz = NpzReader::new(File::open("model.npz")?)?;
+    /// let learning_rate: f64 = npz.scalar_by_name("learning_rate.npy")?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn scalar_by_name<T>(&mut self, name: &str) -> Result<T, ReadNpzError>
+    where
+        T: ReadableElement,
+    {
+        Ok(read_scalar(self.zip.by_name(name)?)?)
+    }
+
     /// Reads an array by index in the `.npz` file.
     pub fn by_index<S, D>(&mut self, index: usize) -> Result<ArrayBase<S, D>, ReadNpzError>
     where
@@ -132,4 +342,164 @@ impl<R: Read + Seek> NpzReader<R> {
     {
         Ok(ArrayBase::<S, D>::read_npy(self.zip.by_index(index)?)?)
     }
+
+    /// Returns the name and a reader of the `.npy` file at `index`.
+    #[cfg(feature = "safetensors")]
+    pub(crate) fn npy_by_index(
+        &mut self,
+        index: usize,
+    ) -> Result<(String, impl Read + '_), ReadNpzError> {
+        let file = self.zip.by_index(index)?;
+        Ok((file.name().to_owned(), file))
+    }
+
+    /// Reads all of the arrays in the file, returning `(name, array)` pairs in
+    /// archive order, i.e. the order of [`names`](#method.names).
+    ///
+    /// The arrays can be read as [`DynArray`] if their element types are not
+    /// known in advance, or as a caller-chosen array type such as
+    /// `ArrayD<f64>`, in which case every array must have that element type.
+    /// If reading an array fails, the error identifies the name of the array.
+    /// Entries with duplicate names are all returned.
+    ///
+    /// [`DynArray`]: enum.DynArray.html
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ndarray::ArrayD;
+    /// use ndarray_npy::{DynArray, NpzReader};
+    /// use std::collections::BTreeMap;
+    /// use std::fs::File;
+    ///
+    /// let mut npz = NpzReader::new(File::open("arrays.npz")?)?;
+    /// let arrays: Vec<(String, DynArray)> = npz.read_all()?;
+    /// for (name, array) in &arrays {
+    ///     println!("{}: {:?}", name, array.shape());
+    /// }
+    ///
+    /// let floats: BTreeMap<String, ArrayD<f64>> = npz.read_all()?.into_iter().collect();
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn read_all<T: ReadNpyExt>(&mut self) -> Result<Vec<(String, T)>, ReadNpzError> {
+        let mut arrays = Vec::with_capacity(self.zip.len());
+        for index in 0..self.zip.len() {
+            let name = self.name_by_index(index)?;
+            let result = match self.zip.by_index(index) {
+                Ok(file) => T::read_npy(file).map_err(ReadNpzError::from),
+                Err(err) => Err(err.into()),
+            };
+            match result {
+                Ok(array) => arrays.push((name, array)),
+                Err(err) => return Err(ReadNpzError::entry(name, err)),
+            }
+        }
+        Ok(arrays)
+    }
+}
+
+impl<B: AsRef<[u8]>> NpzReader<Cursor<B>> {
+    /// Creates a new `.npz` file reader for an in-memory `.npz` file, such as
+    /// a `Vec<u8>` or `bytes::Bytes`.
+    ///
+    /// This is equivalent to `NpzReader::new(Cursor::new(bytes))`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray::{array, Array1};
+    /// use ndarray_npy::{NpzReader, NpzWriter};
+    /// use std::io::Cursor;
+    ///
+    /// let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    /// npz.add_array("a", &array![1, 2, 3])?;
+    /// let bytes = npz.finish()?.into_inner();
+    ///
+    /// let mut npz = NpzReader::from_bytes(bytes)?;
+    /// let a: Array1<i32> = npz.by_name("a")?;
+    /// assert_eq!(a, array![1, 2, 3]);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_bytes(bytes: B) -> Result<NpzReader<Cursor<B>>, ReadNpzError> {
+        NpzReader::new(Cursor::new(bytes))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<R: Read + Seek + Clone + Send + Sync> NpzReader<R> {
+    /// Reads the arrays with the specified names in parallel.
+    ///
+    /// Each worker thread decompresses and decodes entries through its own
+    /// clone of the underlying reader, so `R` should be cheap to clone, e.g. a
+    /// `Cursor<&[u8]>` over the contents of the file or over a memory map of
+    /// the file. The arrays are returned in the same order as `names`.
+    ///
+    /// This method requires the `rayon` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ndarray::Array2;
+    /// use ndarray_npy::NpzReader;
+    /// use std::io::Cursor;
+    ///
+    /// let bytes = std::fs::read("arrays.npz")?;
+    /// let npz = NpzReader::new(Cursor::new(&bytes[..]))?;
+    /// let arrays: Vec<Array2<f64>> = npz.par_by_names(&["a", "b", "c"])?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn par_by_names<S, D>(&self, names: &[&str]) -> Result<Vec<ArrayBase<S, D>>, ReadNpzError>
+    where
+        S::Elem: ReadableElement,
+        S: DataOwned + Send,
+        D: Dimension,
+    {
+        use rayon::prelude::*;
+
+        names
+            .par_iter()
+            .map_init(
+                || self.zip.clone(),
+                |zip, &name| {
+                    let result = match zip.by_name(name) {
+                        Ok(file) => ArrayBase::<S, D>::read_npy(file).map_err(ReadNpzError::from),
+                        Err(err) => Err(err.into()),
+                    };
+                    result.map_err(|err| ReadNpzError::entry(name, err))
+                },
+            )
+            .collect()
+    }
+
+    /// Reads all of the arrays in the file in parallel, returning `(name,
+    /// array)` pairs in archive order.
+    ///
+    /// This is the parallel equivalent of [`read_all`](#method.read_all); see
+    /// [`par_by_names`](#method.par_by_names) for the requirements on `R`.
+    ///
+    /// This method requires the `rayon` feature.
+    pub fn par_read_all<T>(&self) -> Result<Vec<(String, T)>, ReadNpzError>
+    where
+        T: ReadNpyExt + Send,
+    {
+        use rayon::prelude::*;
+
+        (0..self.zip.len())
+            .into_par_iter()
+            .map_init(
+                || self.zip.clone(),
+                |zip, index| {
+                    let name = zip.by_index_raw(index)?.name().to_owned();
+                    let result = match zip.by_index(index) {
+                        Ok(file) => T::read_npy(file).map_err(ReadNpzError::from),
+                        Err(err) => Err(err.into()),
+                    };
+                    match result {
+                        Ok(array) => Ok((name, array)),
+                        Err(err) => Err(ReadNpzError::entry(name, err)),
+                    }
+                },
+            )
+            .collect()
+    }
 }
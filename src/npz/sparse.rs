@@ -0,0 +1,365 @@
+//! Support for sparse matrices in the format written by
+//! [`scipy.sparse.save_npz`].
+//!
+//! [`scipy.sparse.save_npz`]: https://docs.scipy.org/doc/scipy/reference/generated/scipy.sparse.save_npz.html
+
+use super::{NpzReader, NpzWriter, ReadNpzError, WriteNpzError};
+use crate::{Header, ReadDataError, ReadNpyError, ReadableElement, WritableElement, WriteNpyError};
+use ndarray::prelude::*;
+use py_literal::Value as PyValue;
+use std::convert::TryFrom;
+use std::io::{Read, Seek, Write};
+use zip::result::ZipError;
+
+/// A sparse matrix in compressed sparse row (CSR) or compressed sparse column
+/// (CSC) format.
+///
+/// For CSR, `indptr` has one element per row plus one, and `indices` holds
+/// the column index of each element of `data`. For CSC, the roles of rows and
+/// columns are swapped. The fields correspond directly to the arguments of
+/// `sprs::CsMat::new`, which additionally requires the indices within each
+/// row (or column) to be sorted; `scipy` does not always sort them (see
+/// `has_sorted_indices`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsMatrix<A> {
+    /// The number of rows and columns.
+    pub shape: (usize, usize),
+    /// The offsets of each compressed row (or column) in `indices` and `data`.
+    pub indptr: Array1<usize>,
+    /// The column (or row) index of each stored element.
+    pub indices: Array1<usize>,
+    /// The values of the stored elements.
+    pub data: Array1<A>,
+}
+
+/// A sparse matrix in coordinate (COO) format.
+///
+/// The fields correspond directly to the arguments of
+/// `sprs::TriMat::from_triplets`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CooMatrix<A> {
+    /// The number of rows and columns.
+    pub shape: (usize, usize),
+    /// The row index of each stored element.
+    pub row: Array1<usize>,
+    /// The column index of each stored element.
+    pub col: Array1<usize>,
+    /// The values of the stored elements.
+    pub data: Array1<A>,
+}
+
+/// A sparse matrix stored in an `.npz` file by [`scipy.sparse.save_npz`].
+///
+/// [`scipy.sparse.save_npz`]: https://docs.scipy.org/doc/scipy/reference/generated/scipy.sparse.save_npz.html
+#[derive(Clone, Debug, PartialEq)]
+pub enum SparseMatrix<A> {
+    /// Compressed sparse row format (`scipy.sparse.csr_matrix`).
+    Csr(CsMatrix<A>),
+    /// Compressed sparse column format (`scipy.sparse.csc_matrix`).
+    Csc(CsMatrix<A>),
+    /// Coordinate format (`scipy.sparse.coo_matrix`).
+    Coo(CooMatrix<A>),
+}
+
+impl<A> SparseMatrix<A> {
+    /// Returns the number of rows and columns.
+    pub fn shape(&self) -> (usize, usize) {
+        match self {
+            SparseMatrix::Csr(m) | SparseMatrix::Csc(m) => m.shape,
+            SparseMatrix::Coo(m) => m.shape,
+        }
+    }
+
+    /// Returns the name of the format as stored in the `format` entry.
+    fn format_name(&self) -> &'static str {
+        match self {
+            SparseMatrix::Csr(_) => "csr",
+            SparseMatrix::Csc(_) => "csc",
+            SparseMatrix::Coo(_) => "coo",
+        }
+    }
+
+    /// Checks that the lengths of the component arrays are consistent with
+    /// each other and with the shape, that `indptr` is non-decreasing, and
+    /// that all indices are within the shape.
+    fn check(&self) -> Result<(), String> {
+        match self {
+            SparseMatrix::Csr(m) | SparseMatrix::Csc(m) => {
+                let (major, minor) = match self {
+                    SparseMatrix::Csr(_) => m.shape,
+                    _ => (m.shape.1, m.shape.0),
+                };
+                if m.indptr.len() != major + 1 {
+                    return Err(format!(
+                        "indptr has length {}, but expected {}",
+                        m.indptr.len(),
+                        major + 1
+                    ));
+                }
+                if m.indices.len() != m.data.len() {
+                    return Err(format!(
+                        "indices has length {}, but data has length {}",
+                        m.indices.len(),
+                        m.data.len()
+                    ));
+                }
+                if m.indptr[0] != 0 {
+                    return Err(format!("first element of indptr is {}, not 0", m.indptr[0]));
+                }
+                if m.indptr[major] != m.data.len() {
+                    return Err(format!(
+                        "last element of indptr is {}, but data has length {}",
+                        m.indptr[major],
+                        m.data.len()
+                    ));
+                }
+                if m.indptr.windows(2).into_iter().any(|w| w[0] > w[1]) {
+                    return Err("indptr is not non-decreasing".into());
+                }
+                check_bounds("indices", &m.indices, minor)?;
+            }
+            SparseMatrix::Coo(m) => {
+                if m.row.len() != m.data.len() || m.col.len() != m.data.len() {
+                    return Err(format!(
+                        "row, col, and data have different lengths ({}, {}, {})",
+                        m.row.len(),
+                        m.col.len(),
+                        m.data.len()
+                    ));
+                }
+                check_bounds("row", &m.row, m.shape.0)?;
+                check_bounds("col", &m.col, m.shape.1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that all the `indices` are less than `len`.
+fn check_bounds(name: &str, indices: &Array1<usize>, len: usize) -> Result<(), String> {
+    match indices.iter().find(|&&index| index >= len) {
+        Some(index) => Err(format!(
+            "{} contains index {}, but the axis has length {}",
+            name, index, len
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Returns the name of the entry for the array `name`, preferring the
+/// `.npy`-suffixed name written by `numpy.savez`.
+fn entry_name<R: Read + Seek>(npz: &mut NpzReader<R>, name: &str) -> String {
+    let suffixed = format!("{}.npy", name);
+    if npz.zip.by_name(&suffixed).is_ok() {
+        suffixed
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Reads the array `name` with the given element type, wrapping any error with
+/// the name of the entry.
+fn read_entry<R, A>(npz: &mut NpzReader<R>, name: &str) -> Result<Array1<A>, ReadNpzError>
+where
+    R: Read + Seek,
+    A: ReadableElement,
+{
+    let name = entry_name(npz, name);
+    npz.by_name(&name)
+        .map_err(|err| ReadNpzError::entry(name, err))
+}
+
+/// Returns `true` if `err` is a `WrongDescriptor` error reading an entry.
+fn is_wrong_descriptor(err: &ReadNpzError) -> bool {
+    match err {
+        ReadNpzError::Entry { source, .. } => match **source {
+            ReadNpzError::Npy(ReadNpyError::ReadData(ReadDataError::WrongDescriptor(_))) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Reads an index (or shape) array, which `scipy` stores as either `int32` or
+/// `int64`.
+fn read_indices<R: Read + Seek>(
+    npz: &mut NpzReader<R>,
+    name: &str,
+) -> Result<Array1<usize>, ReadNpzError> {
+    let as_usize = |index: i64| {
+        usize::try_from(index)
+            .map_err(|_| ReadNpzError::InvalidSparse(format!("negative value in {}", name)))
+    };
+    match read_entry::<R, i32>(npz, name) {
+        Ok(indices) => indices.iter().map(|&i| as_usize(i64::from(i))).collect(),
+        Err(ref err) if is_wrong_descriptor(err) => read_entry::<R, i64>(npz, name)?
+            .iter()
+            .map(|&i| as_usize(i))
+            .collect(),
+        Err(err) => Err(err),
+    }
+}
+
+/// Reads the `format` entry, which is a 0-dimensional array of ASCII bytes.
+fn read_format<R: Read + Seek>(npz: &mut NpzReader<R>) -> Result<String, ReadNpzError> {
+    let name = entry_name(npz, "format");
+    let mut file = npz
+        .zip
+        .by_name(&name)
+        .map_err(|err| ReadNpzError::entry(name.clone(), err))?;
+    let header = Header::from_reader(&mut file)
+        .map_err(|err| ReadNpzError::entry(name.clone(), ReadNpyError::from(err)))?;
+    match header.type_descriptor {
+        PyValue::String(ref s) if s.trim_start_matches('|').starts_with('S') => {}
+        other => {
+            return Err(ReadNpzError::entry(
+                name,
+                ReadNpyError::from(ReadDataError::WrongDescriptor(other)),
+            ))
+        }
+    }
+    let mut bytes = Vec::new();
+    if let Err(err) = file.read_to_end(&mut bytes) {
+        return Err(ReadNpzError::entry(name, ReadNpyError::from(err)));
+    }
+    // NumPy pads fixed-length byte strings with trailing nulls.
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+impl<R: Read + Seek> NpzReader<R> {
+    /// Reads a sparse matrix from an `.npz` file written by
+    /// [`scipy.sparse.save_npz`].
+    ///
+    /// The index arrays may be stored as either `int32` or `int64`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ndarray_npy::{NpzReader, SparseMatrix};
+    /// use std::fs::File;
+    ///
+    /// let mut npz = NpzReader::new(File::open("adjacency.npz")?)?;
+    /// match npz.read_sparse::<f64>()? {
+    ///     SparseMatrix::Csr(csr) => println!("CSR matrix with {} elements", csr.data.len()),
+    ///     other => println!("{:?} matrix", other.shape()),
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`scipy.sparse.save_npz`]: https://docs.scipy.org/doc/scipy/reference/generated/scipy.sparse.save_npz.html
+    pub fn read_sparse<A: ReadableElement>(&mut self) -> Result<SparseMatrix<A>, ReadNpzError> {
+        let format = read_format(self)?;
+        // `scipy` stores the shape with the platform's default integer type,
+        // which is `int32` on Windows.
+        let shape = read_indices(self, "shape")?;
+        let shape = match shape.as_slice() {
+            Some(&[rows, cols]) => (rows, cols),
+            _ => {
+                return Err(ReadNpzError::InvalidSparse(format!(
+                    "shape has {} elements, but expected 2",
+                    shape.len()
+                )))
+            }
+        };
+        let matrix = match &*format {
+            "csr" | "csc" => {
+                let matrix = CsMatrix {
+                    shape,
+                    indptr: read_indices(self, "indptr")?,
+                    indices: read_indices(self, "indices")?,
+                    data: read_entry(self, "data")?,
+                };
+                if format == "csr" {
+                    SparseMatrix::Csr(matrix)
+                } else {
+                    SparseMatrix::Csc(matrix)
+                }
+            }
+            "coo" => SparseMatrix::Coo(CooMatrix {
+                shape,
+                row: read_indices(self, "row")?,
+                col: read_indices(self, "col")?,
+                data: read_entry(self, "data")?,
+            }),
+            _ => return Err(ReadNpzError::SparseFormat(format)),
+        };
+        matrix.check().map_err(ReadNpzError::InvalidSparse)?;
+        Ok(matrix)
+    }
+}
+
+impl<W: Write + Seek> NpzWriter<W> {
+    /// Writes a sparse matrix in the format of [`scipy.sparse.save_npz`].
+    ///
+    /// The matrix should be the only contents of the `.npz` file. Index
+    /// arrays are written as `int32` if possible and `int64` otherwise,
+    /// matching SciPy.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ndarray::array;
+    /// use ndarray_npy::{CsMatrix, NpzWriter, SparseMatrix};
+    /// use std::fs::File;
+    ///
+    /// let identity = SparseMatrix::Csr(CsMatrix {
+    ///     shape: (3, 3),
+    ///     indptr: array![0, 1, 2, 3],
+    ///     indices: array![0, 1, 2],
+    ///     data: array![1., 1., 1.],
+    /// });
+    /// let mut npz = NpzWriter::new(File::create("identity.npz")?);
+    /// npz.write_sparse(&identity)?;
+    /// npz.finish()?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// [`scipy.sparse.save_npz`]: https://docs.scipy.org/doc/scipy/reference/generated/scipy.sparse.save_npz.html
+    pub fn write_sparse<A: WritableElement>(
+        &mut self,
+        matrix: &SparseMatrix<A>,
+    ) -> Result<(), WriteNpzError> {
+        matrix.check().map_err(WriteNpzError::InvalidSparse)?;
+
+        let (index_arrays, data) = match matrix {
+            SparseMatrix::Csr(m) | SparseMatrix::Csc(m) => {
+                ([("indices", &m.indices), ("indptr", &m.indptr)], &m.data)
+            }
+            SparseMatrix::Coo(m) => ([("row", &m.row), ("col", &m.col)], &m.data),
+        };
+        let (rows, cols) = matrix.shape();
+        let max_index = index_arrays
+            .iter()
+            .flat_map(|(_, indices)| indices.iter())
+            .chain(&[rows, cols])
+            .copied()
+            .max()
+            .unwrap_or(0);
+        let use_i32 = i32::try_from(max_index).is_ok();
+
+        for (name, indices) in &index_arrays {
+            let name = format!("{}.npy", name);
+            if use_i32 {
+                self.add_array(name, &indices.mapv(|i| i as i32))?;
+            } else {
+                self.add_array(name, &indices.mapv(|i| i as i64))?;
+            }
+        }
+        let format = matrix.format_name();
+        self.zip.start_file("format.npy", self.options)?;
+        Header {
+            type_descriptor: PyValue::String(format!("|S{}", format.len())),
+            fortran_order: false,
+            shape: vec![],
+        }
+        .write(&mut self.zip)
+        .map_err(WriteNpyError::from)?;
+        self.zip
+            .write_all(format.as_bytes())
+            .map_err(|err| WriteNpzError::Zip(ZipError::from(err)))?;
+        self.add_array("shape.npy", &array![rows as i64, cols as i64])?;
+        self.add_array("data.npy", data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,784 @@
+//! Conversion between `.npy`/`.npz` files and the [safetensors] format.
+//!
+//! A safetensors file consists of an 8-byte little-endian header length, a
+//! JSON header describing each tensor's element type, shape and byte range,
+//! and a buffer of the raw little-endian tensor data in C order.
+//!
+//! [`SafeTensorsReader`] reads a safetensors file, giving access to each
+//! tensor as an array, as an `.npy` [`Header`] plus data, or as a complete
+//! `.npy` file, and can copy all the tensors into an [`NpzWriter`].
+//! [`SafeTensorsWriter`] collects arrays, `.npy` files or the entries of an
+//! [`NpzReader`] and writes them as a safetensors file. Conversions between
+//! `.npy` and safetensors data work on the raw bytes, so they support every
+//! element type the two formats have in common, converting big-endian and
+//! Fortran-order `.npy` data as needed. The `BF16` and `F8_*` safetensors
+//! types have no `.npy` equivalent.
+//!
+//! This module requires the `safetensors` feature.
+//!
+//! [safetensors]: https://github.com/huggingface/safetensors
+//! [`SafeTensorsReader`]: struct.SafeTensorsReader.html
+//! [`SafeTensorsWriter`]: struct.SafeTensorsWriter.html
+//! [`Header`]: ../struct.Header.html
+//! [`NpzReader`]: ../struct.NpzReader.html
+//! [`NpzWriter`]: ../struct.NpzWriter.html
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ndarray_npy::safetensors::{SafeTensorsReader, SafeTensorsWriter};
+//! use ndarray_npy::{NpzReader, NpzWriter};
+//! use std::fs::File;
+//!
+//! // .npz to safetensors
+//! let mut npz = NpzReader::new(File::open("weights.npz")?)?;
+//! let mut safetensors = SafeTensorsWriter::new(File::create("weights.safetensors")?);
+//! safetensors.add_npz(&mut npz)?;
+//! safetensors.finish()?;
+//!
+//! // safetensors to .npz
+//! let safetensors = SafeTensorsReader::new(File::open("model.safetensors")?)?;
+//! let mut npz = NpzWriter::new(File::create("model.npz")?);
+//! safetensors.write_npz(&mut npz)?;
+//! npz.finish()?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::dtype::{ByteOrder, DType, ScalarType, TypeKind};
+use crate::npy::{c_order_bytes, check_for_extra_bytes};
+use crate::{
+    Header, NpzReader, NpzWriter, ReadNpyError, ReadNpzError, ReadableElement, WritableElement,
+    WriteNpyError, WriteNpzError,
+};
+use ndarray::{Array, ArrayBase, Data, Dimension, IxDyn};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, Write};
+use thiserror::Error;
+
+/// The key of the optional string-to-string metadata map in the header.
+const METADATA_KEY: &str = "__metadata__";
+
+/// The header is padded with spaces so that the data starts at a multiple of
+/// this many bytes.
+const HEADER_ALIGN: usize = 8;
+
+/// The largest header accepted when reading, as in the reference
+/// implementation.
+const MAX_HEADER_LEN: u64 = 100_000_000;
+
+/// An error reading or writing a safetensors file.
+#[derive(Error, Debug)]
+pub enum SafeTensorsError {
+    /// An error caused by I/O.
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+
+    /// The header is not valid JSON.
+    #[error("cannot parse header as JSON")]
+    Json(#[from] serde_json::Error),
+
+    /// The header does not describe valid tensors.
+    #[error("invalid safetensors header: {0}")]
+    InvalidHeader(String),
+
+    /// The element type is not supported by both formats.
+    #[error("unsupported element type: {0}")]
+    UnsupportedDType(String),
+
+    /// There is no tensor with the given name.
+    #[error("no tensor named {0:?}")]
+    MissingTensor(String),
+
+    /// A tensor with the given name has already been added.
+    #[error("duplicate tensor name {0:?}")]
+    DuplicateName(String),
+
+    /// An error reading `.npy` data.
+    #[error("cannot read npy data")]
+    ReadNpy(#[from] ReadNpyError),
+
+    /// An error writing `.npy` data.
+    #[error("cannot write npy data")]
+    WriteNpy(#[from] WriteNpyError),
+
+    /// An error reading an `.npz` file.
+    #[error("cannot read npz file")]
+    ReadNpz(#[from] ReadNpzError),
+
+    /// An error writing an `.npz` file.
+    #[error("cannot write npz file")]
+    WriteNpz(#[from] WriteNpzError),
+}
+
+/// Returns the safetensors name of a scalar `.npy` type.
+fn dtype_name(scalar: &ScalarType) -> Option<&'static str> {
+    Some(match (scalar.kind, scalar.itemsize) {
+        (TypeKind::Bool, 1) => "BOOL",
+        (TypeKind::Int, 1) => "I8",
+        (TypeKind::Int, 2) => "I16",
+        (TypeKind::Int, 4) => "I32",
+        (TypeKind::Int, 8) => "I64",
+        (TypeKind::UInt, 1) => "U8",
+        (TypeKind::UInt, 2) => "U16",
+        (TypeKind::UInt, 4) => "U32",
+        (TypeKind::UInt, 8) => "U64",
+        (TypeKind::Float, 2) => "F16",
+        (TypeKind::Float, 4) => "F32",
+        (TypeKind::Float, 8) => "F64",
+        _ => return None,
+    })
+}
+
+/// Returns the `.npy` type string of a safetensors type.
+fn npy_type_string(dtype: &str) -> Option<&'static str> {
+    Some(match dtype {
+        "BOOL" => "|b1",
+        "I8" => "|i1",
+        "I16" => "<i2",
+        "I32" => "<i4",
+        "I64" => "<i8",
+        "U8" => "|u1",
+        "U16" => "<u2",
+        "U32" => "<u4",
+        "U64" => "<u8",
+        "F16" => "<f2",
+        "F32" => "<f4",
+        "F64" => "<f8",
+        _ => return None,
+    })
+}
+
+/// Returns the size in bytes of an element of a safetensors type, including
+/// the types without an `.npy` equivalent.
+fn itemsize(dtype: &str) -> Option<usize> {
+    match dtype {
+        "BF16" => Some(2),
+        "F8_E4M3" | "F8_E5M2" => Some(1),
+        _ => npy_type_string(dtype).map(|s| s[2..].parse().unwrap()),
+    }
+}
+
+/// Description of a tensor in a safetensors file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TensorInfo {
+    /// The element type, e.g. `"F32"`.
+    pub dtype: String,
+    /// The shape of the tensor.
+    pub shape: Vec<usize>,
+    /// The start and end of the tensor data, in bytes, relative to the start
+    /// of the data buffer.
+    pub data_offsets: (usize, usize),
+}
+
+impl TensorInfo {
+    /// Returns the `.npy` header describing the tensor.
+    pub fn npy_header(&self) -> Result<Header, SafeTensorsError> {
+        let type_string = npy_type_string(&self.dtype)
+            .ok_or_else(|| SafeTensorsError::UnsupportedDType(self.dtype.clone()))?;
+        Ok(Header {
+            type_descriptor: py_literal::Value::String(type_string.into()),
+            fortran_order: false,
+            shape: self.shape.clone(),
+        })
+    }
+
+    fn from_json(name: &str, value: &Value) -> Result<TensorInfo, SafeTensorsError> {
+        let invalid = || SafeTensorsError::InvalidHeader(format!("invalid entry for {:?}", name));
+        let to_usize = |value: &Value| value.as_u64().and_then(|n| usize::try_from(n).ok());
+        let dtype = value["dtype"].as_str().ok_or_else(invalid)?.to_owned();
+        let shape = value["shape"]
+            .as_array()
+            .ok_or_else(invalid)?
+            .iter()
+            .map(to_usize)
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(invalid)?;
+        let offsets = value["data_offsets"]
+            .as_array()
+            .filter(|offsets| offsets.len() == 2)
+            .ok_or_else(invalid)?;
+        let data_offsets = match (to_usize(&offsets[0]), to_usize(&offsets[1])) {
+            (Some(start), Some(end)) if start <= end => (start, end),
+            _ => return Err(invalid()),
+        };
+        Ok(TensorInfo {
+            dtype,
+            shape,
+            data_offsets,
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let mut map = Map::new();
+        map.insert("dtype".into(), self.dtype.clone().into());
+        map.insert("shape".into(), self.shape.clone().into());
+        map.insert(
+            "data_offsets".into(),
+            vec![self.data_offsets.0, self.data_offsets.1].into(),
+        );
+        Value::Object(map)
+    }
+}
+
+/// Reader for safetensors files.
+///
+/// The whole file is read into memory when the reader is created.
+///
+/// # Example
+///
+/// ```no_run
+/// use ndarray::Array2;
+/// use ndarray_npy::safetensors::SafeTensorsReader;
+/// use std::fs::File;
+///
+/// let safetensors = SafeTensorsReader::new(File::open("model.safetensors")?)?;
+/// for name in safetensors.names() {
+///     println!("{}: {:?}", name, safetensors.info(name).unwrap().shape);
+/// }
+/// let weight: Array2<f32> = safetensors.by_name("linear.weight")?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub struct SafeTensorsReader {
+    /// The tensors, ordered by their position in the data buffer.
+    tensors: Vec<(String, TensorInfo)>,
+    metadata: BTreeMap<String, String>,
+    data: Vec<u8>,
+}
+
+impl SafeTensorsReader {
+    /// Reads a safetensors file.
+    ///
+    /// The data ranges of the tensors must match their shapes and element
+    /// types, and must cover the data buffer without gaps or overlaps;
+    /// otherwise, `SafeTensorsError::InvalidHeader` is returned.
+    pub fn new<R: Read>(mut reader: R) -> Result<SafeTensorsReader, SafeTensorsError> {
+        let mut len_bytes = [0; 8];
+        reader.read_exact(&mut len_bytes)?;
+        let header_len = u64::from_le_bytes(len_bytes);
+        if header_len > MAX_HEADER_LEN {
+            return Err(SafeTensorsError::InvalidHeader(format!(
+                "header length {} is too large",
+                header_len
+            )));
+        }
+        let mut header = vec![0; header_len as usize];
+        reader.read_exact(&mut header)?;
+        let header: Value = serde_json::from_slice(&header)?;
+        let header = header
+            .as_object()
+            .ok_or_else(|| SafeTensorsError::InvalidHeader("header is not a JSON object".into()))?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let mut metadata = BTreeMap::new();
+        let mut tensors = Vec::with_capacity(header.len());
+        for (name, value) in header {
+            if name == METADATA_KEY {
+                for (key, value) in value.as_object().into_iter().flatten() {
+                    let value = value.as_str().ok_or_else(|| {
+                        SafeTensorsError::InvalidHeader(format!(
+                            "metadata {:?} is not a string",
+                            key
+                        ))
+                    })?;
+                    metadata.insert(key.clone(), value.to_owned());
+                }
+                continue;
+            }
+            let info = TensorInfo::from_json(name, value)?;
+            let itemsize = itemsize(&info.dtype)
+                .ok_or_else(|| SafeTensorsError::UnsupportedDType(info.dtype.clone()))?;
+            let expected_len = info
+                .shape
+                .iter()
+                .try_fold(itemsize, |len, &n| len.checked_mul(n));
+            let (start, end) = info.data_offsets;
+            if expected_len != Some(end - start) {
+                return Err(SafeTensorsError::InvalidHeader(format!(
+                    "invalid data offsets for {:?}",
+                    name
+                )));
+            }
+            tensors.push((name.clone(), info));
+        }
+        tensors.sort_by_key(|(_, info)| info.data_offsets);
+
+        // The tensors must cover the data buffer without gaps or overlaps.
+        let mut offset = 0;
+        for (name, info) in &tensors {
+            if info.data_offsets.0 != offset {
+                return Err(SafeTensorsError::InvalidHeader(format!(
+                    "data of {:?} does not start where the previous tensor ends",
+                    name
+                )));
+            }
+            offset = info.data_offsets.1;
+        }
+        if offset != data.len() {
+            return Err(SafeTensorsError::InvalidHeader(format!(
+                "tensors cover {} bytes, but the data buffer has {} bytes",
+                offset,
+                data.len()
+            )));
+        }
+        Ok(SafeTensorsReader {
+            tensors,
+            metadata,
+            data,
+        })
+    }
+
+    /// Returns the names of the tensors, in the order of their data in the
+    /// file.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tensors.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the number of tensors.
+    pub fn len(&self) -> usize {
+        self.tensors.len()
+    }
+
+    /// Returns `true` iff the file doesn't contain any tensors.
+    pub fn is_empty(&self) -> bool {
+        self.tensors.is_empty()
+    }
+
+    /// Returns the `__metadata__` map of the header.
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Returns the description of the tensor with the given name.
+    pub fn info(&self, name: &str) -> Option<&TensorInfo> {
+        self.tensors
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, info)| info)
+    }
+
+    /// Returns the `.npy` header and the raw data of the tensor with the given
+    /// name.
+    pub fn npy_parts(&self, name: &str) -> Result<(Header, &[u8]), SafeTensorsError> {
+        let info = self
+            .info(name)
+            .ok_or_else(|| SafeTensorsError::MissingTensor(name.into()))?;
+        let (start, end) = info.data_offsets;
+        Ok((info.npy_header()?, &self.data[start..end]))
+    }
+
+    /// Returns the tensor with the given name as a complete `.npy` file.
+    pub fn npy_bytes(&self, name: &str) -> Result<Vec<u8>, SafeTensorsError> {
+        let (header, data) = self.npy_parts(name)?;
+        let mut npy = header.to_bytes().map_err(WriteNpyError::from)?;
+        npy.extend_from_slice(data);
+        Ok(npy)
+    }
+
+    /// Reads the tensor with the given name as an array.
+    pub fn by_name<A, D>(&self, name: &str) -> Result<Array<A, D>, SafeTensorsError>
+    where
+        A: ReadableElement,
+        D: Dimension,
+    {
+        let (header, data) = self.npy_parts(name)?;
+        let shape = IxDyn(&header.shape);
+        let len = shape.size();
+        let elems = A::read_to_end_exact_vec(data, &header.type_descriptor, len)
+            .map_err(ReadNpyError::from)?;
+        Ok(Array::from_shape_vec(shape, elems)
+            .unwrap()
+            .into_dimensionality()
+            .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, header.shape.len()))?)
+    }
+
+    /// Adds all the tensors to an `.npz` file, in the order of their data in
+    /// the safetensors file.
+    pub fn write_npz<W: Write + Seek>(
+        &self,
+        npz: &mut NpzWriter<W>,
+    ) -> Result<(), SafeTensorsError> {
+        for name in self.names() {
+            npz.add_npy_bytes(name, &self.npy_bytes(name)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writer for safetensors files.
+///
+/// The tensors are held in memory until [`finish`](#method.finish) is
+/// called, because the header containing the data offsets of all the tensors
+/// must be written first.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::safetensors::{SafeTensorsReader, SafeTensorsWriter};
+///
+/// let mut writer = SafeTensorsWriter::new(Vec::new());
+/// writer.add_metadata("format", "pt");
+/// writer.add_array("bias", &array![0.5f32, -0.5])?;
+/// let bytes = writer.finish()?;
+///
+/// let reader = SafeTensorsReader::new(&bytes[..])?;
+/// assert_eq!(reader.info("bias").unwrap().dtype, "F32");
+/// assert_eq!(reader.metadata()["format"], "pt");
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub struct SafeTensorsWriter<W: Write> {
+    writer: W,
+    tensors: Vec<(String, TensorInfo)>,
+    metadata: BTreeMap<String, String>,
+    data: Vec<u8>,
+}
+
+impl<W: Write> SafeTensorsWriter<W> {
+    /// Creates a new safetensors file writer.
+    pub fn new(writer: W) -> SafeTensorsWriter<W> {
+        SafeTensorsWriter {
+            writer,
+            tensors: Vec::new(),
+            metadata: BTreeMap::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Adds an entry to the `__metadata__` map of the header.
+    pub fn add_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.metadata.insert(key.into(), value.into());
+    }
+
+    /// Appends the tensor's data, which is written by `write_data`, and
+    /// records its description.
+    fn push<F>(
+        &mut self,
+        name: String,
+        dtype: &str,
+        shape: Vec<usize>,
+        write_data: F,
+    ) -> Result<(), SafeTensorsError>
+    where
+        F: FnOnce(&mut Vec<u8>) -> Result<(), SafeTensorsError>,
+    {
+        if self.tensors.iter().any(|(n, _)| *n == name) || name == METADATA_KEY {
+            return Err(SafeTensorsError::DuplicateName(name));
+        }
+        let start = self.data.len();
+        if let Err(err) = write_data(&mut self.data) {
+            self.data.truncate(start);
+            return Err(err);
+        }
+        let info = TensorInfo {
+            dtype: dtype.into(),
+            shape,
+            data_offsets: (start, self.data.len()),
+        };
+        self.tensors.push((name, info));
+        Ok(())
+    }
+
+    /// Adds an array with the specified `name`.
+    pub fn add_array<N, A, S, D>(
+        &mut self,
+        name: N,
+        array: &ArrayBase<S, D>,
+    ) -> Result<(), SafeTensorsError>
+    where
+        N: Into<String>,
+        A: WritableElement + Clone,
+        S: Data<Elem = A>,
+        D: Dimension,
+    {
+        let descr = A::type_descriptor_with_order(ByteOrder::Little)
+            .ok_or_else(|| SafeTensorsError::UnsupportedDType(A::type_descriptor().to_string()))?;
+        let dtype = match DType::from_descr(&descr) {
+            Ok(DType::Scalar(ref scalar)) => dtype_name(scalar),
+            _ => None,
+        }
+        .ok_or_else(|| SafeTensorsError::UnsupportedDType(descr.to_string()))?;
+        self.push(name.into(), dtype, array.shape().to_vec(), |data| {
+            let array = array.as_standard_layout();
+            A::write_slice_with_order(array.as_slice().unwrap(), data, ByteOrder::Little)
+                .map_err(WriteNpyError::from)?;
+            Ok(())
+        })
+    }
+
+    /// Adds the array of an `.npy` file read from `reader` with the specified
+    /// `name`, converting the data to little-endian C order if necessary.
+    pub fn add_npy<N, R>(&mut self, name: N, mut reader: R) -> Result<(), SafeTensorsError>
+    where
+        N: Into<String>,
+        R: Read,
+    {
+        let header = Header::from_reader(&mut reader).map_err(ReadNpyError::from)?;
+        let scalar = match header.dtype() {
+            Ok(DType::Scalar(scalar)) => scalar,
+            _ => {
+                return Err(SafeTensorsError::UnsupportedDType(
+                    header.type_descriptor.to_string(),
+                ))
+            }
+        };
+        let dtype = dtype_name(&scalar)
+            .ok_or_else(|| SafeTensorsError::UnsupportedDType(scalar.to_string()))?;
+        let num_bytes = header
+            .shape
+            .iter()
+            .try_fold(scalar.itemsize, |len, &n| len.checked_mul(n))
+            .ok_or(ReadNpyError::LengthOverflow)?;
+        self.push(name.into(), dtype, header.shape.clone(), |data| {
+            let start = data.len();
+            data.resize(start + num_bytes, 0);
+            let bytes = &mut data[start..];
+            reader.read_exact(bytes).map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => {
+                    ReadNpyError::from(crate::ReadDataError::MissingData)
+                }
+                _ => ReadNpyError::from(err),
+            })?;
+            check_for_extra_bytes(&mut reader).map_err(ReadNpyError::from)?;
+            if scalar.byte_order == ByteOrder::Big {
+                for elem in bytes.chunks_exact_mut(scalar.itemsize) {
+                    elem.reverse();
+                }
+            }
+            if header.fortran_order && header.shape.len() > 1 {
+                let reordered = c_order_bytes(bytes, &header.shape, scalar.itemsize);
+                bytes.copy_from_slice(&reordered);
+            }
+            Ok(())
+        })
+    }
+
+    /// Adds all the arrays of an `.npz` file, in archive order.
+    ///
+    /// The tensor names are the names of the arrays without the `.npy`
+    /// extension that `numpy.savez` adds.
+    pub fn add_npz<R: Read + Seek>(
+        &mut self,
+        npz: &mut NpzReader<R>,
+    ) -> Result<(), SafeTensorsError> {
+        for index in 0..npz.len() {
+            let (name, reader) = npz.npy_by_index(index)?;
+            let name = if name.ends_with(".npy") {
+                name[..name.len() - ".npy".len()].to_owned()
+            } else {
+                name
+            };
+            self.add_npy(name, reader)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the header and the tensor data, and returns the underlying
+    /// writer.
+    pub fn finish(mut self) -> Result<W, SafeTensorsError> {
+        let mut header = Map::new();
+        if !self.metadata.is_empty() {
+            let metadata = self
+                .metadata
+                .into_iter()
+                .map(|(key, value)| (key, Value::String(value)))
+                .collect();
+            header.insert(METADATA_KEY.into(), Value::Object(metadata));
+        }
+        for (name, info) in &self.tensors {
+            header.insert(name.clone(), info.to_json());
+        }
+        let mut header = serde_json::to_vec(&Value::Object(header))?;
+        let padded_len = (header.len() + HEADER_ALIGN - 1) / HEADER_ALIGN * HEADER_ALIGN;
+        header.resize(padded_len, b' ');
+        self.writer
+            .write_all(&(header.len() as u64).to_le_bytes())?;
+        self.writer.write_all(&header)?;
+        self.writer.write_all(&self.data)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SafeTensorsError, SafeTensorsReader, SafeTensorsWriter};
+    use crate::{NpzReader, NpzWriter, WriteNpyExt, WriteOptions};
+    use ndarray::{array, Array1, Array2, Array3, ArrayD};
+    use std::io::Cursor;
+
+    #[test]
+    fn array_round_trip() {
+        let mut writer = SafeTensorsWriter::new(Vec::new());
+        let weight = array![[1.5f32, -2.], [3., 4.25], [5., 6.]];
+        writer.add_array("weight", &weight.t()).unwrap();
+        writer.add_array("mask", &array![true, false]).unwrap();
+        writer.add_array("steps", &array![[1i64], [2]]).unwrap();
+        match writer.add_array("mask", &array![1u8]) {
+            Err(SafeTensorsError::DuplicateName(name)) => assert_eq!(name, "mask"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let bytes = writer.finish().unwrap();
+        let header_len = u64::from_le_bytes([
+            bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+        ]);
+        assert_eq!(header_len % 8, 0);
+
+        let reader = SafeTensorsReader::new(&bytes[..]).unwrap();
+        assert_eq!(
+            reader.names().collect::<Vec<_>>(),
+            ["weight", "mask", "steps"]
+        );
+        let info = reader.info("weight").unwrap();
+        assert_eq!(info.dtype, "F32");
+        assert_eq!(info.shape, [2, 3]);
+        assert_eq!(info.data_offsets, (0, 24));
+        let read: Array2<f32> = reader.by_name("weight").unwrap();
+        assert_eq!(read, weight.t());
+        let read: Array1<bool> = reader.by_name("mask").unwrap();
+        assert_eq!(read, array![true, false]);
+        let read: ArrayD<i64> = reader.by_name("steps").unwrap();
+        assert_eq!(read, array![[1i64], [2]].into_dyn());
+        match reader.by_name::<f64, ndarray::Ix2>("weight") {
+            Err(SafeTensorsError::ReadNpy(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match reader.by_name::<f32, ndarray::Ix2>("bias") {
+            Err(SafeTensorsError::MissingTensor(name)) => assert_eq!(name, "bias"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn npz_round_trip() {
+        let a = array![[[1u16, 2], [3, 4]], [[5, 6], [7, 8]]];
+        let b = array![[1.5f64, 2.5, 3.5], [4.5, 5.5, 6.5]];
+        let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+        npz.add_array("a.npy", &a).unwrap();
+        npz.add_array("b.npy", &b).unwrap();
+        let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+
+        let mut writer = SafeTensorsWriter::new(Vec::new());
+        writer.add_npz(&mut npz).unwrap();
+        let bytes = writer.finish().unwrap();
+        let reader = SafeTensorsReader::new(&bytes[..]).unwrap();
+        assert_eq!(reader.names().collect::<Vec<_>>(), ["a", "b"]);
+
+        let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+        reader.write_npz(&mut npz).unwrap();
+        let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+        assert_eq!(npz.names().unwrap(), ["a", "b"]);
+        let read: Array3<u16> = npz.by_name("a").unwrap();
+        assert_eq!(read, a);
+        let read: Array2<f64> = npz.by_name("b").unwrap();
+        assert_eq!(read, b);
+    }
+
+    #[test]
+    fn npy_conversion() {
+        // Big-endian, Fortran-order data is converted to little-endian C order.
+        let arr = array![[1i32, -2, 3], [4, 5, -6]];
+        let mut npy = Vec::new();
+        arr.t()
+            .write_npy_with_options(
+                &mut npy,
+                &WriteOptions::new().byte_order(crate::dtype::ByteOrder::Big),
+            )
+            .unwrap();
+        let mut writer = SafeTensorsWriter::new(Vec::new());
+        writer.add_npy("x", &npy[..]).unwrap();
+        let bytes = writer.finish().unwrap();
+        let reader = SafeTensorsReader::new(&bytes[..]).unwrap();
+        let (header, data) = reader.npy_parts("x").unwrap();
+        assert_eq!(header.shape, [3, 2]);
+        assert!(!header.fortran_order);
+        let expected: Vec<u8> = arr
+            .t()
+            .iter()
+            .flat_map(|x: &i32| x.to_le_bytes().to_vec())
+            .collect();
+        assert_eq!(data, &expected[..]);
+        let read: Array2<i32> =
+            crate::ReadNpyExt::read_npy(&reader.npy_bytes("x").unwrap()[..]).unwrap();
+        assert_eq!(read, arr.t());
+
+        let truncated = &npy[..npy.len() - 1];
+        assert!(SafeTensorsWriter::new(Vec::new())
+            .add_npy("x", truncated)
+            .is_err());
+    }
+
+    /// Assembles a safetensors file from a header and `data_len` zero bytes.
+    fn safetensors_file(header: &[u8], data_len: usize) -> Vec<u8> {
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header);
+        bytes.resize(bytes.len() + data_len, 0);
+        bytes
+    }
+
+    #[test]
+    fn unsupported_dtype() {
+        let header = br#"{"x":{"dtype":"BF16","shape":[2],"data_offsets":[0,4]}}"#;
+        let bytes = safetensors_file(header, 4);
+        let reader = SafeTensorsReader::new(&bytes[..]).unwrap();
+        assert_eq!(reader.info("x").unwrap().dtype, "BF16");
+        match reader.npy_bytes("x") {
+            Err(SafeTensorsError::UnsupportedDType(dtype)) => assert_eq!(dtype, "BF16"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let header = br#"{"x":{"dtype":"X7","shape":[2],"data_offsets":[0,4]}}"#;
+        match SafeTensorsReader::new(&safetensors_file(header, 4)[..]) {
+            Err(SafeTensorsError::UnsupportedDType(dtype)) => assert_eq!(dtype, "X7"),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        let header = br#"{"x":{"dtype":"F32","shape":[2],"data_offsets":[0,4]}}"#;
+        match SafeTensorsReader::new(&safetensors_file(header, 4)[..]) {
+            Err(SafeTensorsError::InvalidHeader(_)) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn invalid_layout() {
+        let valid = br#"{"a":{"dtype":"U8","shape":[2],"data_offsets":[2,4]},"b":{"dtype":"U8","shape":[2],"data_offsets":[0,2]}}"#;
+        let reader = SafeTensorsReader::new(&safetensors_file(valid, 4)[..]).unwrap();
+        assert_eq!(reader.names().collect::<Vec<_>>(), ["b", "a"]);
+
+        for &(header, data_len) in &[
+            // overlapping
+            (
+                &br#"{"a":{"dtype":"U8","shape":[2],"data_offsets":[0,2]},"b":{"dtype":"U8","shape":[2],"data_offsets":[1,3]}}"#[..],
+                3,
+            ),
+            // gap
+            (
+                &br#"{"a":{"dtype":"U8","shape":[2],"data_offsets":[0,2]},"b":{"dtype":"U8","shape":[2],"data_offsets":[3,5]}}"#[..],
+                5,
+            ),
+            // not starting at zero
+            (
+                &br#"{"a":{"dtype":"U8","shape":[2],"data_offsets":[1,3]}}"#[..],
+                3,
+            ),
+            // trailing bytes
+            (
+                &br#"{"a":{"dtype":"U8","shape":[2],"data_offsets":[0,2]}}"#[..],
+                3,
+            ),
+            // missing bytes
+            (
+                &br#"{"a":{"dtype":"U8","shape":[2],"data_offsets":[0,2]}}"#[..],
+                1,
+            ),
+        ] {
+            match SafeTensorsReader::new(&safetensors_file(header, data_len)[..]) {
+                Err(SafeTensorsError::InvalidHeader(_)) => {}
+                other => panic!("unexpected result: {:?}", other.map(|_| ())),
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Serialization of arrays as embedded `.npy` data with [`serde`].
+//!
+//! The functions in this module are meant to be used with serde's
+//! `#[serde(with = "...")]` field attribute. The array is written in `.npy`
+//! format to a byte string, so it can be stored inside a larger structure
+//! serialized with any serde data format (e.g. bincode or MessagePack), and
+//! the embedded bytes are identical to the contents of a `.npy` file written
+//! by [`write_npy`].
+//!
+//! This module requires the `serde` feature.
+//!
+//! [`serde`]: https://serde.rs
+//! [`write_npy`]: ../fn.write_npy.html
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::{array, Array1, Array2};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Checkpoint {
+//!     step: u64,
+//!     #[serde(with = "ndarray_npy::serde")]
+//!     weights: Array2<f32>,
+//!     #[serde(with = "ndarray_npy::serde")]
+//!     bias: Array1<f32>,
+//! }
+//!
+//! let checkpoint = Checkpoint {
+//!     step: 100,
+//!     weights: array![[1., 2.], [3., 4.]],
+//!     bias: array![0.5, -0.5],
+//! };
+//! let json = serde_json::to_string(&checkpoint)?;
+//! let restored: Checkpoint = serde_json::from_str(&json)?;
+//! assert_eq!(restored.weights, checkpoint.weights);
+//! assert_eq!(restored.bias, checkpoint.bias);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{ReadNpyExt, ReadableElement, WritableElement, WriteNpyExt};
+use ndarray::{Array, ArrayBase, Data, Dimension};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Serializes an array as a byte string containing its `.npy` representation.
+pub fn serialize<A, S, D, Ser>(
+    array: &ArrayBase<S, D>,
+    serializer: Ser,
+) -> Result<Ser::Ok, Ser::Error>
+where
+    A: WritableElement,
+    S: Data<Elem = A>,
+    D: Dimension,
+    Ser: Serializer,
+{
+    let mut bytes = Vec::new();
+    array.write_npy(&mut bytes).map_err(ser::Error::custom)?;
+    serializer.serialize_bytes(&bytes)
+}
+
+/// Deserializes an array from a byte string containing its `.npy`
+/// representation.
+///
+/// Formats without a native byte string type, such as JSON, represent the
+/// bytes as a sequence of integers; these are accepted too.
+pub fn deserialize<'de, A, D, De>(deserializer: De) -> Result<Array<A, D>, De::Error>
+where
+    A: ReadableElement,
+    D: Dimension,
+    De: Deserializer<'de>,
+{
+    deserializer.deserialize_bytes(NpyVisitor(PhantomData))
+}
+
+struct NpyVisitor<A, D>(PhantomData<(A, D)>);
+
+impl<'de, A, D> Visitor<'de> for NpyVisitor<A, D>
+where
+    A: ReadableElement,
+    D: Dimension,
+{
+    type Value = Array<A, D>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a byte string containing .npy data")
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        Array::read_npy(bytes).map_err(E::custom)
+    }
+
+    fn visit_seq<V: SeqAccess<'de>>(self, mut seq: V) -> Result<Self::Value, V::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Header;
+    use ndarray::{array, Array1, Array3, ArrayD};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Checkpoint {
+        name: String,
+        #[serde(with = "crate::serde")]
+        weights: Array3<f64>,
+        #[serde(with = "crate::serde")]
+        mask: Array1<bool>,
+        header: Header,
+    }
+
+    fn checkpoint() -> Checkpoint {
+        let weights = array![[[1., 2.], [3., 4.]], [[5., 6.], [7., 8.]]].reversed_axes();
+        let header = Header {
+            type_descriptor: <f64 as crate::WritableElement>::type_descriptor(),
+            fortran_order: true,
+            shape: weights.shape().to_vec(),
+        };
+        Checkpoint {
+            name: "layer".into(),
+            weights,
+            mask: array![true, false, true],
+            header,
+        }
+    }
+
+    fn check(restored: &Checkpoint, original: &Checkpoint) {
+        assert_eq!(restored.name, original.name);
+        assert_eq!(restored.weights, original.weights);
+        assert_eq!(restored.mask, original.mask);
+        assert_eq!(
+            restored.header.type_descriptor,
+            original.header.type_descriptor
+        );
+        assert_eq!(restored.header.fortran_order, original.header.fortran_order);
+        assert_eq!(restored.header.shape, original.header.shape);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let original = checkpoint();
+        let json = serde_json::to_string(&original).unwrap();
+        check(&serde_json::from_str(&json).unwrap(), &original);
+    }
+
+    #[test]
+    fn invalid_data() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            #[serde(with = "crate::serde")]
+            _array: ArrayD<f32>,
+        }
+        let json = serde_json::to_string(&(b"not npy".to_vec(),)).unwrap();
+        assert!(serde_json::from_str::<Wrapper>(&json).is_err());
+    }
+}
@@ -0,0 +1,362 @@
+//! Conversion of 1-D and 2-D arrays to and from delimited text such as CSV.
+//!
+//! These functions are the Rust equivalents of [`numpy.savetxt`] and
+//! [`numpy.loadtxt`]. Each row of a 2-D array is written as one line of text,
+//! and a 1-D array is written as one element per line. The delimiter, float
+//! precision and an optional header line are configured with
+//! [`TextOptions`].
+//!
+//! [`numpy.savetxt`]: https://numpy.org/doc/stable/reference/generated/numpy.savetxt.html
+//! [`numpy.loadtxt`]: https://numpy.org/doc/stable/reference/generated/numpy.loadtxt.html
+//! [`TextOptions`]: struct.TextOptions.html
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::{array, Array2};
+//! use ndarray_npy::text::{read_txt, write_txt, TextOptions};
+//!
+//! let options = TextOptions::new().precision(1).header("x,y");
+//! let mut csv = Vec::new();
+//! write_txt(&mut csv, &array![[1., 2.], [3., 4.]], &options)?;
+//! assert_eq!(String::from_utf8(csv.clone())?, "# x,y\n1.0,2.0\n3.0,4.0\n");
+//!
+//! let arr: Array2<f64> = read_txt(&csv[..], &options)?;
+//! assert_eq!(arr, array![[1., 2.], [3., 4.]]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    ReadNpyError, ReadNpyExt, ReadableElement, WritableElement, WriteNpyError, WriteNpyExt,
+};
+use ndarray::{ArrayBase, ArrayD, Axis, Data, Dimension, IxDyn};
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// An error converting an array to or from text.
+#[derive(Error, Debug)]
+pub enum TextError {
+    /// An error caused by I/O.
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+
+    /// An error reading the `.npy` input.
+    #[error("cannot read .npy data")]
+    ReadNpy(#[from] ReadNpyError),
+
+    /// An error writing the `.npy` output.
+    #[error("cannot write .npy data")]
+    WriteNpy(#[from] WriteNpyError),
+
+    /// Only 1-D and 2-D arrays can be written as text.
+    #[error("cannot convert a {0}-D array to text")]
+    Ndim(usize),
+
+    /// A field of the text cannot be parsed as an element.
+    #[error("line {line}, column {column}: cannot parse {field:?}: {message}")]
+    Parse {
+        line: usize,
+        column: usize,
+        field: String,
+        message: String,
+    },
+
+    /// A line has a different number of fields than the first line.
+    #[error("line {line}: expected {expected} fields, found {found}")]
+    ColumnCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    /// The text is not a single row or column, so it cannot be read as a 1-D
+    /// array.
+    #[error("cannot read {rows}x{columns} values as a 1-D array")]
+    NotOneDimensional { rows: usize, columns: usize },
+}
+
+/// Options for converting arrays to and from text.
+///
+/// The defaults match `numpy.savetxt`/`numpy.loadtxt` with
+/// `delimiter=','`: elements are separated by commas and formatted with the
+/// shortest representation that round-trips, and lines starting with `#` are
+/// treated as comments when reading.
+#[derive(Clone, Debug)]
+pub struct TextOptions {
+    delimiter: char,
+    precision: Option<usize>,
+    header: Option<String>,
+    comments: String,
+    skip_rows: usize,
+}
+
+impl TextOptions {
+    /// Creates the default options.
+    pub fn new() -> TextOptions {
+        TextOptions {
+            delimiter: ',',
+            precision: None,
+            header: None,
+            comments: "# ".into(),
+            skip_rows: 0,
+        }
+    }
+
+    /// Sets the delimiter between fields, e.g. `'\t'` for tab-separated
+    /// values.
+    pub fn delimiter(mut self, delimiter: char) -> TextOptions {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the number of digits after the decimal point when writing
+    /// floating point elements. This has no effect on integers.
+    pub fn precision(mut self, precision: usize) -> TextOptions {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Sets a header line, such as column names, to write before the data.
+    /// Each line of the header is prefixed with the comment string.
+    pub fn header(mut self, header: impl Into<String>) -> TextOptions {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Sets the comment string, which defaults to `"# "`. It prefixes the
+    /// header when writing, and lines starting with it (ignoring surrounding
+    /// whitespace) are skipped when reading. Use an empty string to write a
+    /// plain header row, e.g. for spreadsheets, together with
+    /// [`skip_rows`](#method.skip_rows) to skip it when reading.
+    pub fn comments(mut self, comments: impl Into<String>) -> TextOptions {
+        self.comments = comments.into();
+        self
+    }
+
+    /// Sets the number of lines to skip at the start when reading.
+    pub fn skip_rows(mut self, skip_rows: usize) -> TextOptions {
+        self.skip_rows = skip_rows;
+        self
+    }
+}
+
+impl Default for TextOptions {
+    fn default() -> TextOptions {
+        TextOptions::new()
+    }
+}
+
+/// Writes a 1-D or 2-D array to `writer` as delimited text.
+pub fn write_txt<W, A, S, D>(
+    mut writer: W,
+    array: &ArrayBase<S, D>,
+    options: &TextOptions,
+) -> Result<(), TextError>
+where
+    W: io::Write,
+    A: Display,
+    S: Data<Elem = A>,
+    D: Dimension,
+{
+    let array = match array.ndim() {
+        1 => array.view().into_dyn().insert_axis(Axis(1)),
+        2 => array.view().into_dyn(),
+        ndim => return Err(TextError::Ndim(ndim)),
+    };
+    let mut writer = io::BufWriter::new(&mut writer);
+    if let Some(header) = &options.header {
+        for line in header.lines() {
+            writeln!(writer, "{}{}", options.comments, line)?;
+        }
+    }
+    for row in array.outer_iter() {
+        for (i, elem) in row.iter().enumerate() {
+            if i > 0 {
+                write!(writer, "{}", options.delimiter)?;
+            }
+            match options.precision {
+                Some(precision) => write!(writer, "{:.*}", precision, elem)?,
+                None => write!(writer, "{}", elem)?,
+            }
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads delimited text from `reader` into an array.
+///
+/// The result is 2-D unless `D` is `Ix1`, in which case the text must be a
+/// single row or a single column. Empty lines and comment lines are skipped.
+pub fn read_txt<R, A, D>(
+    reader: R,
+    options: &TextOptions,
+) -> Result<ndarray::Array<A, D>, TextError>
+where
+    R: io::Read,
+    A: FromStr,
+    A::Err: Display,
+    D: Dimension,
+{
+    let comment = options.comments.trim();
+    let mut data = Vec::new();
+    let mut columns = None;
+    let mut rows = 0;
+    for (index, line) in io::BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line_num = index + 1;
+        let trimmed = line.trim();
+        if index < options.skip_rows
+            || trimmed.is_empty()
+            || (!comment.is_empty() && trimmed.starts_with(comment))
+        {
+            continue;
+        }
+        let mut found = 0;
+        for (column, field) in trimmed.split(options.delimiter).enumerate() {
+            let field = field.trim();
+            let elem = field.parse().map_err(|err: A::Err| TextError::Parse {
+                line: line_num,
+                column: column + 1,
+                field: field.into(),
+                message: err.to_string(),
+            })?;
+            data.push(elem);
+            found += 1;
+        }
+        match columns {
+            None => columns = Some(found),
+            Some(expected) if expected != found => {
+                return Err(TextError::ColumnCount {
+                    line: line_num,
+                    expected,
+                    found,
+                })
+            }
+            Some(_) => {}
+        }
+        rows += 1;
+    }
+    let columns = columns.unwrap_or(0);
+    let shape = if D::NDIM == Some(1) {
+        if rows > 1 && columns > 1 {
+            return Err(TextError::NotOneDimensional { rows, columns });
+        }
+        IxDyn(&[data.len()])
+    } else {
+        IxDyn(&[rows, columns])
+    };
+    let array = ArrayD::from_shape_vec(shape, data).unwrap();
+    array
+        .into_dimensionality()
+        .map_err(|_| TextError::Ndim(D::NDIM.unwrap_or(2)))
+}
+
+/// Converts a 1-D or 2-D `.npy` file read from `reader` to delimited text.
+///
+/// # Example
+///
+/// ```no_run
+/// use ndarray_npy::text::{npy_to_txt, TextOptions};
+/// use std::fs::File;
+///
+/// let options = TextOptions::new().precision(3);
+/// npy_to_txt::<_, _, f64>(File::open("array.npy")?, File::create("array.csv")?, &options)?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn npy_to_txt<R, W, A>(reader: R, writer: W, options: &TextOptions) -> Result<(), TextError>
+where
+    R: io::Read,
+    W: io::Write,
+    A: ReadableElement + Display,
+{
+    let array = ArrayD::<A>::read_npy(reader)?;
+    write_txt(writer, &array, options)
+}
+
+/// Converts delimited text read from `reader` to a 2-D `.npy` file.
+pub fn txt_to_npy<R, W, A>(reader: R, writer: W, options: &TextOptions) -> Result<(), TextError>
+where
+    R: io::Read,
+    W: io::Write,
+    A: FromStr + WritableElement,
+    A::Err: Display,
+{
+    let array: ndarray::Array2<A> = read_txt(reader, options)?;
+    array.write_npy(writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_txt, write_txt, TextError, TextOptions};
+    use ndarray::{array, Array1, Array2, Array3};
+
+    #[test]
+    fn write_options() {
+        let mut out = Vec::new();
+        let options = TextOptions::new()
+            .delimiter('\t')
+            .comments("")
+            .header("a\tb");
+        write_txt(&mut out, &array![[1, 2], [3, 4]], &options).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "a\tb\n1\t2\n3\t4\n");
+
+        let mut out = Vec::new();
+        write_txt(
+            &mut out,
+            &array![1.25f32, -0.5],
+            &TextOptions::new().precision(2),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1.25\n-0.50\n");
+
+        match write_txt(
+            Vec::new(),
+            &Array3::<i32>::zeros((1, 1, 1)),
+            &TextOptions::new(),
+        ) {
+            Err(TextError::Ndim(3)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_options() {
+        let text = "x;y\n# comment\n1; 2\n\n3 ;4\n";
+        let options = TextOptions::new().delimiter(';').skip_rows(1);
+        let arr: Array2<i64> = read_txt(text.as_bytes(), &options).unwrap();
+        assert_eq!(arr, array![[1, 2], [3, 4]]);
+
+        let arr: Array1<f64> = read_txt("1.5,2.5,3".as_bytes(), &TextOptions::new()).unwrap();
+        assert_eq!(arr, array![1.5, 2.5, 3.]);
+        match read_txt::<_, f64, ndarray::Ix1>(text.as_bytes(), &options) {
+            Err(TextError::NotOneDimensional {
+                rows: 2,
+                columns: 2,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_errors() {
+        match read_txt::<_, i32, ndarray::Ix2>("1,2\n3,x\n".as_bytes(), &TextOptions::new()) {
+            Err(TextError::Parse {
+                line: 2, column: 2, ..
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_txt::<_, i32, ndarray::Ix2>("1,2\n3\n".as_bytes(), &TextOptions::new()) {
+            Err(TextError::ColumnCount {
+                line: 2,
+                expected: 2,
+                found: 1,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "cli")]
+
+use ndarray::{array, Array2, ArrayD};
+use ndarray_npy::{read_npy, write_npy, NpzReader, NpzWriter};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Creates an empty temporary directory for a test.
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ndarray-npy-cli-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs the tool with the given arguments, returning its standard output.
+fn run(args: &[&dyn AsRef<std::ffi::OsStr>]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ndarray-npy"))
+        .args(args.iter().map(|arg| arg.as_ref()))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn example(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name)
+}
+
+#[test]
+fn info() {
+    let out = run(&[&"info", &example("example_f64_big_endian_fortran.npy")]);
+    assert_eq!(
+        out,
+        "version: 1.0\ndtype: '>f8'\nshape: [2, 3, 4]\norder: Fortran\n"
+    );
+}
+
+#[test]
+fn convert_byte_order() {
+    let dir = temp_dir("convert");
+    let converted = dir.join("big.npy");
+    run(&[
+        &"convert",
+        &example("example_f64_little_endian_standard.npy"),
+        &converted,
+        &"--byte-order",
+        &"big",
+    ]);
+    assert_eq!(
+        fs::read(&converted).unwrap(),
+        fs::read(example("example_f64_big_endian_standard.npy")).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn csv_round_trip() {
+    let dir = temp_dir("csv");
+    let npy = dir.join("a.npy");
+    let csv = dir.join("a.csv");
+    let arr = array![[1.5, -2.], [3., 4.25]];
+    write_npy(&npy, &arr).unwrap();
+    run(&[&"to-csv", &npy, &csv]);
+    assert_eq!(fs::read_to_string(&csv).unwrap(), "1.5,-2\n3,4.25\n");
+    run(&[&"from-csv", &csv, &npy]);
+    let read: Array2<f64> = read_npy(&npy).unwrap();
+    assert_eq!(read, arr);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn npz_ls_and_convert() {
+    let dir = temp_dir("npz");
+    let npz_path = dir.join("arrays.npz");
+    let mut npz = NpzWriter::new(File::create(&npz_path).unwrap());
+    npz.add_array("a.npy", &array![1u8, 2, 3]).unwrap();
+    npz.add_array("b.npy", &array![[1i32, 2], [3, 4]]).unwrap();
+    npz.finish().unwrap();
+
+    let out = run(&[&"ls", &npz_path]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("a.npy\t'|u1'\t[3]\tC\t"));
+    assert!(lines[1].starts_with("b.npy\t'<i4'\t[2, 2]\tC\t"));
+
+    let out = run(&[&"slice", &npz_path, &"-m", &"b", &"1,::-1"]);
+    assert_eq!(out.trim(), "[4, 3]");
+
+    let compressed = dir.join("compressed.npz");
+    run(&[&"convert", &npz_path, &compressed, &"--compress"]);
+    let mut npz = NpzReader::new(File::open(&compressed).unwrap()).unwrap();
+    assert_eq!(npz.names().unwrap(), ["a.npy", "b.npy"]);
+    let b: ArrayD<i32> = npz.by_name("b.npy").unwrap();
+    assert_eq!(b, array![[1, 2], [3, 4]].into_dyn());
+    fs::remove_dir_all(&dir).unwrap();
+}
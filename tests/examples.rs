@@ -1,5 +1,6 @@
 use ndarray::prelude::*;
-use ndarray_npy::{ReadNpyExt, WriteNpyExt};
+use ndarray_npy::dtype::ByteOrder;
+use ndarray_npy::{write_npy_atomic, ReadNpyExt, WriteNpyError, WriteNpyExt, WriteOptions};
 use std::io::Cursor;
 
 #[test]
@@ -34,6 +35,83 @@ fn write_f64_fortran() {
     assert_eq!(&correct[..], &writer[..]);
 }
 
+#[test]
+fn write_f64_byte_order() {
+    let mut arr = Array3::<f64>::zeros((2, 3, 4));
+    for (i, elem) in arr.iter_mut().enumerate() {
+        *elem = i as f64;
+    }
+    for &(byte_order, correct) in &[
+        (
+            ByteOrder::Little,
+            &include_bytes!("example_f64_little_endian_standard.npy")[..],
+        ),
+        (
+            ByteOrder::Big,
+            &include_bytes!("example_f64_big_endian_standard.npy")[..],
+        ),
+    ] {
+        let mut writer = Vec::<u8>::new();
+        let options = WriteOptions::new().byte_order(byte_order);
+        arr.write_npy_with_options(&mut writer, &options).unwrap();
+        assert_eq!(correct, &writer[..]);
+        // Non-contiguous arrays are written element by element.
+        let mut wide = Array3::<f64>::zeros((2, 3, 8));
+        wide.slice_mut(s![.., .., ..;2]).assign(&arr);
+        let mut writer = Vec::<u8>::new();
+        wide.slice(s![.., .., ..;2])
+            .write_npy_with_options(&mut writer, &options)
+            .unwrap();
+        assert_eq!(correct, &writer[..]);
+    }
+}
+
+#[test]
+fn write_options_default_impl() {
+    // A type that implements only the required `write_npy` method.
+    struct Wrapper(Array1<f64>);
+
+    impl WriteNpyExt for Wrapper {
+        fn write_npy<W: std::io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+            self.0.write_npy(writer)
+        }
+    }
+
+    let wrapper = Wrapper(array![1., 2.]);
+    let mut writer = Vec::<u8>::new();
+    wrapper
+        .write_npy_with_options(&mut writer, &WriteOptions::new())
+        .unwrap();
+    assert_eq!(Array1::<f64>::read_npy(&writer[..]).unwrap(), wrapper.0);
+
+    let options = WriteOptions::new().header_padding(64);
+    match wrapper.write_npy_with_options(Vec::<u8>::new(), &options) {
+        Err(WriteNpyError::UnsupportedOptions) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn write_f64_atomic() {
+    #[cfg(target_endian = "little")]
+    let correct = include_bytes!("example_f64_little_endian_standard.npy");
+    #[cfg(target_endian = "big")]
+    let correct = include_bytes!("example_f64_big_endian_standard.npy");
+
+    let dir = std::env::temp_dir().join(format!("ndarray-npy-examples-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("example.npy");
+    std::fs::write(&path, b"old contents").unwrap();
+    let mut arr = Array3::<f64>::zeros((2, 3, 4));
+    for (i, elem) in arr.iter_mut().enumerate() {
+        *elem = i as f64;
+    }
+    write_npy_atomic(&path, &arr).unwrap();
+    assert_eq!(&correct[..], &std::fs::read(&path).unwrap()[..]);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[test]
 fn read_f64_standard() {
     let mut correct = Array3::<f64>::zeros((2, 3, 4));
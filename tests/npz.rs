@@ -0,0 +1,381 @@
+#![cfg(feature = "npz")]
+
+use ndarray::prelude::*;
+use ndarray_npy::{
+    CompressionMethod, CooMatrix, CsMatrix, DynArray, Header, NpzReader, NpzWriter, ReadNpyError,
+    ReadNpzError, SparseMatrix, WritableElement, WriteNpyExt, WriteNpzError,
+};
+use py_literal::Value as PyValue;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+#[test]
+fn write_all_read_all() {
+    let mut arrays = BTreeMap::<String, ArrayD<i32>>::new();
+    arrays.insert("b".into(), array![[1, 2, 3], [4, 5, 6]].into_dyn());
+    arrays.insert("a".into(), array![7, 8, 9].into_dyn());
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::<u8>::new()));
+    npz.write_all(&arrays).unwrap();
+    let writer = npz.finish().unwrap();
+
+    let mut npz = NpzReader::new(writer).unwrap();
+    assert_eq!(npz.names().unwrap(), vec!["a", "b"]);
+    let read: Vec<(String, ArrayD<i32>)> = npz.read_all().unwrap();
+    assert_eq!(read, arrays.into_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn scalar_by_name() {
+    let mut npz = NpzWriter::new(Cursor::new(Vec::<u8>::new()));
+    npz.add_array("lr", &arr0(0.25f64)).unwrap();
+    npz.add_array("epochs", &array![10i64]).unwrap();
+    npz.add_array("betas", &array![0.9f64, 0.999]).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    assert_eq!(npz.scalar_by_name::<f64>("lr").unwrap(), 0.25);
+    assert_eq!(npz.scalar_by_name::<i64>("epochs").unwrap(), 10);
+    match npz.scalar_by_name::<f64>("betas") {
+        Err(ReadNpzError::Npy(ReadNpyError::WrongShape(shape))) => assert_eq!(shape, [2]),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn read_all_dyn() {
+    let mut npz = NpzWriter::new(Cursor::new(Vec::<u8>::new()));
+    npz.add_array("ints", &array![1i32, 2, 3]).unwrap();
+    npz.add_array("floats", &array![[1f64, 2.], [3., 4.]])
+        .unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    let read: Vec<(String, DynArray)> = npz.read_all().unwrap();
+    assert_eq!(
+        read,
+        vec![
+            ("ints".into(), DynArray::I32(array![1, 2, 3].into_dyn())),
+            (
+                "floats".into(),
+                DynArray::F64(array![[1., 2.], [3., 4.]].into_dyn())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn read_all_duplicate_names() {
+    let mut npz = NpzWriter::new(Cursor::new(Vec::<u8>::new()));
+    npz.add_array("a", &array![1i32]).unwrap();
+    npz.add_array("b", &array![2i32]).unwrap();
+    npz.add_array("a", &array![3i32]).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    let read: Vec<(String, Array1<i32>)> = npz.read_all().unwrap();
+    assert_eq!(
+        read,
+        vec![
+            ("a".into(), array![1]),
+            ("b".into(), array![2]),
+            ("a".into(), array![3]),
+        ]
+    );
+}
+
+#[test]
+fn read_all_reports_entry() {
+    let mut writer = Cursor::new(Vec::<u8>::new());
+    {
+        let mut npz = NpzWriter::new(&mut writer);
+        npz.add_array("ints", &array![1i32, 2, 3]).unwrap();
+        npz.add_array("floats", &array![1f64, 2., 3.]).unwrap();
+    }
+
+    let mut npz = NpzReader::new(writer).unwrap();
+    let result: Result<Vec<(String, Array1<i32>)>, _> = npz.read_all();
+    match result {
+        Err(ReadNpzError::Entry { name, source }) => {
+            assert_eq!(name, "floats");
+            match *source {
+                ReadNpzError::Npy(_) => {}
+                other => panic!("unexpected source: {:?}", other),
+            }
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_read_all() {
+    let mut arrays = BTreeMap::<String, Array2<f64>>::new();
+    for i in 0..20 {
+        let array = Array2::from_shape_fn((10, 7), |(j, k)| (i * 100 + j * 10 + k) as f64);
+        arrays.insert(format!("arr_{}", i), array);
+    }
+
+    let mut writer = Cursor::new(Vec::<u8>::new());
+    NpzWriter::new(&mut writer).write_all(&arrays).unwrap();
+    let bytes = writer.into_inner();
+
+    let npz = NpzReader::new(Cursor::new(&bytes[..])).unwrap();
+    let read: Vec<(String, Array2<f64>)> = npz.par_read_all().unwrap();
+    assert_eq!(read, arrays.clone().into_iter().collect::<Vec<_>>());
+    let some: Vec<Array2<f64>> = npz.par_by_names(&["arr_3", "arr_17"]).unwrap();
+    assert_eq!(
+        some,
+        vec![arrays["arr_3"].clone(), arrays["arr_17"].clone()]
+    );
+}
+
+#[cfg(feature = "compressed_npz")]
+#[test]
+fn entries_compressed() {
+    let mut npz = NpzWriter::new_compressed(Cursor::new(Vec::<u8>::new()));
+    npz.add_array("a", &Array1::<f64>::zeros(100)).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    let entries = npz.entries().unwrap();
+    assert_eq!(entries[0].compression, CompressionMethod::Deflated);
+    assert!(entries[0].compressed_size < entries[0].size);
+}
+
+#[test]
+fn entries() {
+    let mut writer = Cursor::new(Vec::<u8>::new());
+    {
+        let mut npz = NpzWriter::new(&mut writer);
+        npz.add_array("a", &Array2::<f64>::zeros((3, 4).f()))
+            .unwrap();
+        npz.add_array("b", &array![1u8, 2, 3]).unwrap();
+    }
+
+    let mut npz = NpzReader::new(writer).unwrap();
+    let entries = npz.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+
+    assert_eq!(entries[0].name, "a");
+    assert_eq!(entries[0].compression, CompressionMethod::Stored);
+    assert_eq!(entries[0].size, 128 + 3 * 4 * 8);
+    assert_eq!(entries[0].compressed_size, entries[0].size);
+    assert_eq!(entries[0].header.shape, vec![3, 4]);
+    assert!(entries[0].header.fortran_order);
+
+    assert_eq!(entries[1].name, "b");
+    assert_eq!(entries[1].header.shape, vec![3]);
+    assert_eq!(
+        entries[1].header.type_descriptor.as_string().unwrap(),
+        "|u1"
+    );
+}
+
+#[test]
+fn read_zip64() {
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    let arr = array![[1i64, 2], [3, 4]];
+    let mut npy = Vec::<u8>::new();
+    arr.write_npy(&mut npy).unwrap();
+
+    let mut writer = Cursor::new(Vec::<u8>::new());
+    {
+        let mut zip = zip::ZipWriter::new(&mut writer);
+        zip.start_file("arr", FileOptions::default().large_file(true))
+            .unwrap();
+        zip.write_all(&npy).unwrap();
+        zip.finish().unwrap();
+    }
+
+    let mut npz = NpzReader::new(writer).unwrap();
+    let read: Array2<i64> = npz.by_name("arr").unwrap();
+    assert_eq!(read, arr);
+}
+
+#[test]
+#[ignore]
+fn write_large_entry() {
+    let len = (1 << 32) + 16;
+    let arr = Array1::<u8>::from_elem(len, 7);
+    let mut writer = std::io::BufWriter::new(std::fs::File::create("large.npz").unwrap());
+    {
+        let mut npz = NpzWriter::new(&mut writer);
+        npz.add_array("large", &arr).unwrap();
+        npz.add_array("small", &array![1u8, 2, 3]).unwrap();
+    }
+    drop(writer);
+
+    let mut npz = NpzReader::new(std::fs::File::open("large.npz").unwrap()).unwrap();
+    let entries = npz.entries().unwrap();
+    assert_eq!(entries[0].header.shape, vec![len]);
+    assert!(entries[0].size > u64::from(std::u32::MAX));
+    let small: Array1<u8> = npz.by_name("small").unwrap();
+    assert_eq!(small, array![1, 2, 3]);
+    drop(npz);
+    std::fs::remove_file("large.npz").unwrap();
+}
+
+#[test]
+fn sparse_round_trip() {
+    let csr = SparseMatrix::Csr(CsMatrix {
+        shape: (3, 4),
+        indptr: array![0, 2, 2, 3],
+        indices: array![1, 3, 0],
+        data: array![1.5f64, -2., 4.],
+    });
+    let coo = SparseMatrix::Coo(CooMatrix {
+        shape: (2, 5),
+        row: array![0, 1, 1],
+        col: array![4, 0, 2],
+        data: array![7i64, 8, 9],
+    });
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::<u8>::new()));
+    npz.write_sparse(&csr).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    let mut names = npz.names().unwrap();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "data.npy",
+            "format.npy",
+            "indices.npy",
+            "indptr.npy",
+            "shape.npy"
+        ]
+    );
+    assert_eq!(npz.read_sparse::<f64>().unwrap(), csr);
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::<u8>::new()));
+    npz.write_sparse(&coo).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    assert_eq!(npz.read_sparse::<i64>().unwrap(), coo);
+}
+
+/// Writes a 2×2 CSC matrix as SciPy does, with the shape and index arrays
+/// converted to the integer type `I` by `cast`.
+fn write_scipy_csc<I: WritableElement>(
+    cast: fn(i64) -> I,
+    indptr: Array1<i64>,
+    indices: Array1<i64>,
+) -> Cursor<Vec<u8>> {
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    let mut writer = Cursor::new(Vec::<u8>::new());
+    {
+        let mut zip = zip::ZipWriter::new(&mut writer);
+        zip.start_file("format.npy", FileOptions::default())
+            .unwrap();
+        Header {
+            type_descriptor: PyValue::String("|S3".into()),
+            fortran_order: false,
+            shape: vec![],
+        }
+        .write(&mut zip)
+        .unwrap();
+        zip.write_all(b"csc").unwrap();
+        for (name, arr) in &[
+            ("shape.npy", array![2i64, 2]),
+            ("indptr.npy", indptr),
+            ("indices.npy", indices),
+        ] {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            arr.mapv(cast).write_npy(&mut zip).unwrap();
+        }
+        zip.start_file("data.npy", FileOptions::default()).unwrap();
+        array![3f32, 4.].write_npy(&mut zip).unwrap();
+        zip.finish().unwrap();
+    }
+    writer
+}
+
+/// Checks reading a CSC matrix written as SciPy does, with the shape and index
+/// arrays converted to the integer type `I` by `cast`.
+fn check_read_sparse_indices<I: WritableElement>(cast: fn(i64) -> I) {
+    let writer = write_scipy_csc(cast, array![0, 1, 2], array![1, 0]);
+    let mut npz = NpzReader::new(writer).unwrap();
+    assert_eq!(
+        npz.read_sparse::<f32>().unwrap(),
+        SparseMatrix::Csc(CsMatrix {
+            shape: (2, 2),
+            indptr: array![0, 1, 2],
+            indices: array![1, 0],
+            data: array![3., 4.],
+        })
+    );
+}
+
+#[test]
+fn read_sparse_int64_indices() {
+    check_read_sparse_indices(|i| i);
+}
+
+#[test]
+fn read_sparse_int32_indices() {
+    // SciPy uses `int32` for the shape and indices on Windows.
+    check_read_sparse_indices(|i| i as i32);
+}
+
+#[test]
+fn write_sparse_invalid() {
+    let bad = [
+        // `indptr` is too short.
+        SparseMatrix::Csr(CsMatrix {
+            shape: (3, 3),
+            indptr: array![0, 1, 2],
+            indices: array![0, 1],
+            data: array![1u8, 2],
+        }),
+        // `indptr` is decreasing.
+        SparseMatrix::Csr(CsMatrix {
+            shape: (2, 3),
+            indptr: array![0, 2, 1],
+            indices: array![0],
+            data: array![1u8],
+        }),
+        // A column index is out of bounds.
+        SparseMatrix::Csr(CsMatrix {
+            shape: (2, 3),
+            indptr: array![0, 1, 2],
+            indices: array![0, 3],
+            data: array![1u8, 2],
+        }),
+        // A row index is out of bounds.
+        SparseMatrix::Csc(CsMatrix {
+            shape: (2, 3),
+            indptr: array![0, 1, 1, 1],
+            indices: array![2],
+            data: array![1u8],
+        }),
+        SparseMatrix::Coo(CooMatrix {
+            shape: (2, 3),
+            row: array![0, 2],
+            col: array![0, 0],
+            data: array![1u8, 2],
+        }),
+    ];
+    for matrix in &bad {
+        let mut npz = NpzWriter::new(Cursor::new(Vec::<u8>::new()));
+        match npz.write_sparse(matrix) {
+            Err(WriteNpzError::InvalidSparse(_)) => {}
+            other => panic!("unexpected result for {:?}: {:?}", matrix, other),
+        }
+    }
+}
+
+#[test]
+fn read_sparse_invalid() {
+    let bad = [
+        // A row index is out of bounds.
+        (array![0, 1, 2], array![1, 2]),
+        // `indptr` is decreasing.
+        (array![0, 3, 2], array![1, 0]),
+        // `indptr` does not start at zero.
+        (array![1, 1, 2], array![1, 0]),
+    ];
+    for (indptr, indices) in bad.iter().cloned() {
+        let writer = write_scipy_csc(|i| i, indptr, indices);
+        let mut npz = NpzReader::new(writer).unwrap();
+        match npz.read_sparse::<f32>() {
+            Err(ReadNpzError::InvalidSparse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use ndarray::prelude::*;
 use ndarray::{array, Data};
+use ndarray_npy::datetime::{DateTime64, Microseconds, TimeDelta64};
 use ndarray_npy::{ReadNpyExt, ReadableElement, WritableElement, WriteNpyExt};
 use std::fmt::Debug;
 
@@ -48,3 +49,18 @@ fn round_trip_bool() {
         [[false], [true], [false]]
     ]);
 }
+
+#[test]
+fn round_trip_datetime64() {
+    test_round_trip(&array![
+        [
+            DateTime64::<Microseconds>::new(1_600_000_000_000_000),
+            DateTime64::NAT
+        ],
+        [DateTime64::new(-5), DateTime64::new(0)],
+    ]);
+    test_round_trip(&array![
+        TimeDelta64::<Microseconds>::new(-7),
+        TimeDelta64::NAT
+    ]);
+}
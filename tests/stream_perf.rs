@@ -1,17 +1,17 @@
 use ndarray_npy::NpyOutStreamBuilder;
 use std::time::{Duration, Instant};
 
-fn mean(numbers: &Vec<Duration>) -> Duration {
+fn mean(numbers: &[Duration]) -> Duration {
     let sum: Duration = numbers.iter().sum();
     sum / (numbers.len() as u32)
 }
 
-fn median(numbers: &mut Vec<Duration>) -> Duration {
+fn median(numbers: &mut [Duration]) -> Duration {
     numbers.sort();
 
     let mid = numbers.len() / 2;
     if numbers.len() % 2 == 0 {
-        mean(&vec![numbers[mid - 1], numbers[mid]])
+        mean(&[numbers[mid - 1], numbers[mid]])
     } else {
         numbers[mid]
     }